@@ -0,0 +1,201 @@
+// BM25 lexical ranking over chunks
+//
+// Every chunk is treated as a "document". Document frequencies are gathered
+// across all chunks of all walked files, so a term that appears everywhere
+// contributes little while rare terms dominate the ranking.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::Chunk;
+
+/// Term frequency saturation
+const K1: f64 = 1.2;
+/// Document length normalization
+const B: f64 = 0.75;
+
+/// A single token with its position in the source text (in characters)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub term: String,
+    pub start: usize,
+    pub len: usize,
+}
+
+/// Split text into lowercase alphanumeric tokens, keeping character positions
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut start = 0;
+    let mut len = 0;
+
+    for (i, ch) in text.chars().enumerate() {
+        if ch.is_alphanumeric() {
+            if len == 0 {
+                start = i;
+            }
+            current.extend(ch.to_lowercase());
+            len += 1;
+        } else if len > 0 {
+            tokens.push(Token {
+                term: std::mem::take(&mut current),
+                start,
+                len,
+            });
+            len = 0;
+        }
+    }
+
+    if len > 0 {
+        tokens.push(Token {
+            term: current,
+            start,
+            len,
+        });
+    }
+
+    tokens
+}
+
+/// Corpus statistics needed to compute BM25 for a given query.
+///
+/// Only document frequencies of the query terms are tracked, which keeps
+/// the statistics small no matter how large the corpus is.
+#[derive(Debug, Clone)]
+pub struct Bm25Stats {
+    terms: Vec<String>,
+    doc_count: usize,
+    total_len: usize,
+    doc_freqs: HashMap<String, usize>,
+}
+
+impl Bm25Stats {
+    /// Create empty statistics for a query
+    pub fn new(query: &str) -> Self {
+        let mut terms: Vec<String> = tokenize(query).into_iter().map(|t| t.term).collect();
+        terms.sort();
+        terms.dedup();
+
+        Self {
+            terms,
+            doc_count: 0,
+            total_len: 0,
+            doc_freqs: HashMap::new(),
+        }
+    }
+
+    /// Build statistics from a set of chunks
+    pub fn from_chunks<'a>(query: &str, chunks: impl IntoIterator<Item = &'a Chunk>) -> Self {
+        let mut stats = Self::new(query);
+        for chunk in chunks {
+            stats.add_chunk(chunk);
+        }
+        stats
+    }
+
+    /// Account for one more chunk in the corpus
+    pub fn add_chunk(&mut self, chunk: &Chunk) {
+        let tokens = tokenize(chunk.search_text());
+        self.doc_count += 1;
+        self.total_len += tokens.len();
+
+        for term in &self.terms {
+            if tokens.iter().any(|t| &t.term == term) {
+                *self.doc_freqs.entry(term.clone()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Account for all chunks of a file at once, see `FileTermStats`
+    pub fn add_file(&mut self, file: &FileTermStats) {
+        self.doc_count += file.chunks;
+        self.total_len += file.tokens;
+        for term in &self.terms {
+            if let Some(df) = file.chunk_freqs.get(term) {
+                *self.doc_freqs.entry(term.clone()).or_insert(0) += df;
+            }
+        }
+    }
+
+    fn avg_doc_len(&self) -> f64 {
+        if self.doc_count == 0 {
+            return 0.0;
+        }
+        self.total_len as f64 / self.doc_count as f64
+    }
+
+    /// Inverse document frequency (BM25+ style, always positive)
+    fn idf(&self, term: &str) -> f64 {
+        let n = self.doc_count as f64;
+        let df = self.doc_freqs.get(term).copied().unwrap_or(0) as f64;
+        (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+    }
+
+    /// Score a chunk and return the character positions of all matched query terms
+    pub fn score(&self, chunk: &Chunk) -> (f64, Option<Vec<usize>>) {
+        let tokens = tokenize(chunk.search_text());
+        let avg_len = self.avg_doc_len();
+        let length_norm = if avg_len > 0.0 {
+            1.0 - B + B * (tokens.len() as f64 / avg_len)
+        } else {
+            1.0
+        };
+
+        let mut score = 0.0;
+        let mut indices = Vec::new();
+
+        for term in &self.terms {
+            let mut tf = 0;
+            for token in tokens.iter().filter(|t| &t.term == term) {
+                tf += 1;
+                indices.extend(token.start..token.start + token.len);
+            }
+
+            if tf > 0 {
+                let tf = tf as f64;
+                score += self.idf(term) * (tf * (K1 + 1.0)) / (tf + K1 * length_norm);
+            }
+        }
+
+        if indices.is_empty() {
+            return (0.0, None);
+        }
+
+        indices.sort_unstable();
+        (score, Some(indices))
+    }
+
+    /// Score of an average-length chunk containing every query term once.
+    /// Used to normalize BM25 scores into [0, 1].
+    pub fn optimal_score(&self) -> f64 {
+        self.terms.iter().map(|t| self.idf(t)).sum()
+    }
+}
+
+/// Query independent statistics of one file's chunks. Stored in the index,
+/// so `Bm25Stats` can be gathered without tokenizing every chunk again
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct FileTermStats {
+    chunks: usize,
+    tokens: usize,
+    // Number of chunks containing each term
+    chunk_freqs: HashMap<String, usize>,
+}
+
+impl FileTermStats {
+    pub fn from_chunks(chunks: &[Chunk]) -> Self {
+        let mut stats = Self::default();
+        for chunk in chunks {
+            let tokens = tokenize(chunk.search_text());
+            stats.chunks += 1;
+            stats.tokens += tokens.len();
+
+            let terms: HashSet<String> = tokens.into_iter().map(|t| t.term).collect();
+            for term in terms {
+                *stats.chunk_freqs.entry(term).or_insert(0) += 1;
+            }
+        }
+        stats
+    }
+}
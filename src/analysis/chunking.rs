@@ -0,0 +1,322 @@
+// Boundary-aware chunking
+//
+// Instead of cutting every `window_size` chars, text is split into sentences
+// (ending in . ! or ? followed by whitespace) and paragraphs (separated by a
+// blank line). Consecutive sentences are packed into chunks of at most
+// `window_size` chars, so snippets start and end where the text does.
+// Sentences longer than the window fall back to fixed windows.
+//
+// Markdown files are first split into sections at ATX headings ("# Title"),
+// each chunk remembering its heading path. Fenced code blocks are never split.
+
+use super::{Chunk, SlidingWindow, fixed_chunks};
+
+/// A sentence, in char indices, trimmed of surrounding whitespace
+#[derive(Debug, Clone, PartialEq)]
+struct Sentence {
+    start: usize,
+    end: usize,
+    // First sentence after a blank line
+    new_paragraph: bool,
+}
+
+fn is_closing(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '"' | '\'' | ')' | ']')
+}
+
+/// True if the line starting after the newline at `i` is blank,
+/// returning the index of the newline that ends it
+fn blank_line_end(chars: &[char], i: usize) -> Option<usize> {
+    let mut j = i + 1;
+    while j < chars.len() && chars[j] != '\n' && chars[j].is_whitespace() {
+        j += 1;
+    }
+    (j < chars.len() && chars[j] == '\n').then_some(j)
+}
+
+fn split_sentences(chars: &[char]) -> Vec<Sentence> {
+    let mut sentences = Vec::new();
+    let mut start: Option<usize> = None;
+    let mut end = 0;
+    let mut new_paragraph = true;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if c.is_whitespace() {
+            if c == '\n'
+                && let Some(j) = blank_line_end(chars, i)
+            {
+                if let Some(s) = start.take() {
+                    sentences.push(Sentence {
+                        start: s,
+                        end,
+                        new_paragraph,
+                    });
+                }
+                new_paragraph = true;
+                i = j + 1;
+                continue;
+            }
+            i += 1;
+            continue;
+        }
+
+        let s = *start.get_or_insert(i);
+        end = i + 1;
+
+        if matches!(c, '.' | '!' | '?') {
+            // Keep closing quotes, brackets and "?!" with the sentence
+            let mut j = i + 1;
+            while j < chars.len() && is_closing(chars[j]) {
+                j += 1;
+            }
+            if j == chars.len() || chars[j].is_whitespace() {
+                sentences.push(Sentence {
+                    start: s,
+                    end: j,
+                    new_paragraph,
+                });
+                start = None;
+                new_paragraph = false;
+                i = j;
+                continue;
+            }
+        }
+
+        i += 1;
+    }
+
+    if let Some(s) = start {
+        sentences.push(Sentence {
+            start: s,
+            end,
+            new_paragraph,
+        });
+    }
+
+    sentences
+}
+
+fn make_chunk(chars: &[char], start: usize, end: usize, heading_path: Option<&str>) -> Chunk {
+    Chunk {
+        text: chars[start..end].iter().collect(),
+        start_char: start,
+        end_char: end,
+        heading_path: heading_path.map(str::to_string),
+        ..Default::default()
+    }
+}
+
+/// Split text into chunks of whole sentences, at most `window_size` chars each.
+///
+/// A paragraph break starts a new chunk once the current one is at least half
+/// full, so short paragraphs are kept together.
+pub fn sentence_chunks(content: &str, window: &SlidingWindow) -> Vec<Chunk> {
+    let chars: Vec<char> = content.chars().collect();
+    let max = window.window_size.max(1);
+
+    let mut chunks = Vec::new();
+    let mut current: Option<(usize, usize)> = None;
+
+    for sentence in split_sentences(&chars) {
+        if sentence.end - sentence.start > max {
+            if let Some((s, e)) = current.take() {
+                chunks.push(make_chunk(&chars, s, e, None));
+            }
+            let text: String = chars[sentence.start..sentence.end].iter().collect();
+            chunks.extend(fixed_chunks(&text, window).into_iter().map(|mut c| {
+                c.start_char += sentence.start;
+                c.end_char += sentence.start;
+                c
+            }));
+            continue;
+        }
+
+        current = match current {
+            Some((s, e))
+                if sentence.end - s <= max && !(sentence.new_paragraph && e - s >= max / 2) =>
+            {
+                Some((s, sentence.end))
+            }
+            Some((s, e)) => {
+                chunks.push(make_chunk(&chars, s, e, None));
+                Some((sentence.start, sentence.end))
+            }
+            None => Some((sentence.start, sentence.end)),
+        };
+    }
+
+    if let Some((s, e)) = current {
+        chunks.push(make_chunk(&chars, s, e, None));
+    }
+
+    chunks
+}
+
+/// A paragraph, heading line or fenced code block, in char indices
+#[derive(Debug, Clone, PartialEq)]
+struct Block {
+    start: usize,
+    end: usize,
+    // Fenced code, kept in one piece
+    fenced: bool,
+}
+
+/// Blocks under one heading
+#[derive(Debug, Default)]
+struct Section {
+    heading_path: Option<String>,
+    blocks: Vec<Block>,
+}
+
+/// Level and title of an ATX heading line ("## Title ##")
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let level = rest.chars().take_while(|&c| c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let title = &rest[level..];
+    if !title.is_empty() && !title.starts_with([' ', '\t']) {
+        return None;
+    }
+    let title = title.trim().trim_end_matches('#').trim_end();
+    Some((level, title.to_string()))
+}
+
+/// Fence marker character and length
+type Fence = (char, usize);
+
+/// Opening fence marker ("```" or "~~~", possibly longer)
+fn parse_fence(line: &str) -> Option<Fence> {
+    let trimmed = line.trim_start();
+    let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'))?;
+    let len = trimmed.chars().take_while(|&c| c == marker).count();
+    (len >= 3).then_some((marker, len))
+}
+
+fn split_sections(content: &str) -> Vec<Section> {
+    let mut sections = vec![Section::default()];
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    // Open paragraph or fence: (start, end, fence marker)
+    let mut open: Option<(usize, usize, Option<Fence>)> = None;
+
+    let mut pos = 0;
+    for line in content.split('\n') {
+        let start = pos;
+        let end = start + line.chars().count();
+        pos = end + 1;
+
+        if let Some((s, _, Some((marker, len)))) = open {
+            let closing = parse_fence(line).is_some_and(|(m, l)| {
+                m == marker && l >= len && line.trim().chars().all(|c| c == m)
+            });
+            if closing {
+                open = None;
+                push_block(&mut sections, s, end, true);
+            } else {
+                open = Some((s, end, Some((marker, len))));
+            }
+            continue;
+        }
+
+        let fence = parse_fence(line);
+        let heading = parse_heading(line);
+        if (fence.is_some() || heading.is_some() || line.trim().is_empty())
+            && let Some((s, e, _)) = open.take()
+        {
+            push_block(&mut sections, s, e, false);
+        }
+
+        if fence.is_some() {
+            open = Some((start, end, fence));
+        } else if let Some((level, title)) = heading {
+            headings.retain(|(l, _)| *l < level);
+            headings.push((level, title));
+            let path: Vec<&str> = headings
+                .iter()
+                .map(|(_, t)| t.as_str())
+                .filter(|t| !t.is_empty())
+                .collect();
+            sections.push(Section {
+                heading_path: (!path.is_empty()).then(|| path.join(" > ")),
+                blocks: vec![],
+            });
+            push_block(&mut sections, start, end, false);
+        } else if !line.trim().is_empty() {
+            open = Some(match open {
+                Some((s, _, _)) => (s, end, None),
+                None => (start, end, None),
+            });
+        }
+    }
+
+    // Unterminated fences run to the end of the file
+    if let Some((s, e, fence)) = open {
+        push_block(&mut sections, s, e, fence.is_some());
+    }
+
+    sections
+}
+
+fn push_block(sections: &mut [Section], start: usize, end: usize, fenced: bool) {
+    if let Some(section) = sections.last_mut() {
+        section.blocks.push(Block { start, end, fenced });
+    }
+}
+
+/// Split Markdown into chunks that never cross a heading.
+///
+/// Blocks of a section are packed up to `window_size` chars. Oversized
+/// paragraphs are split by sentences, fenced code blocks stay whole.
+pub fn markdown_chunks(content: &str, window: &SlidingWindow) -> Vec<Chunk> {
+    let chars: Vec<char> = content.chars().collect();
+    let max = window.window_size.max(1);
+    let mut chunks = Vec::new();
+
+    for section in split_sections(content) {
+        let heading = section.heading_path.as_deref();
+        let mut current: Option<(usize, usize)> = None;
+
+        for block in section.blocks {
+            if block.end - block.start > max {
+                if let Some((s, e)) = current.take() {
+                    chunks.push(make_chunk(&chars, s, e, heading));
+                }
+                if block.fenced {
+                    chunks.push(make_chunk(&chars, block.start, block.end, heading));
+                } else {
+                    let text: String = chars[block.start..block.end].iter().collect();
+                    chunks.extend(sentence_chunks(&text, window).into_iter().map(|mut c| {
+                        c.start_char += block.start;
+                        c.end_char += block.start;
+                        c.heading_path = section.heading_path.clone();
+                        c
+                    }));
+                }
+                continue;
+            }
+
+            current = match current {
+                Some((s, _)) if block.end - s <= max => Some((s, block.end)),
+                Some((s, e)) => {
+                    chunks.push(make_chunk(&chars, s, e, heading));
+                    Some((block.start, block.end))
+                }
+                None => Some((block.start, block.end)),
+            };
+        }
+
+        if let Some((s, e)) = current {
+            chunks.push(make_chunk(&chars, s, e, heading));
+        }
+    }
+
+    chunks
+}
@@ -0,0 +1,753 @@
+// Source-code-aware chunking
+//
+// A small lexer classifies every char of a source file as code, comment or
+// string literal. Brace languages are split where the brace depth returns
+// to the current level, Python where the indentation does. Oversized items
+// (impl blocks, classes) are split again one level deeper, so methods end up
+// in their own chunks. Each chunk is tagged with the item it belongs to.
+//
+// The same classification is used to restrict a search to comments, string
+// literals or identifiers: everything else is blanked out of the text that
+// gets scored, so indices still line up with the original chunk.
+
+use std::{ops::Range, sync::LazyLock};
+
+use regex::Regex;
+
+use super::{Chunk, SlidingWindow, fixed_chunks};
+use crate::config::CodeScope;
+
+/// Items nested deeper than this are cut into fixed windows
+const MAX_NESTING: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Rust,
+    Python,
+    Java,
+    // C and C++
+    C,
+    // JavaScript and TypeScript
+    JavaScript,
+}
+
+impl Language {
+    /// Language of a file extension (without the dot), as listed in `ALLOWED_UTF8_FILE_EXTS`
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext {
+            "rs" => Some(Language::Rust),
+            "py" => Some(Language::Python),
+            "java" => Some(Language::Java),
+            "c" | "cpp" => Some(Language::C),
+            "js" | "ts" => Some(Language::JavaScript),
+            _ => None,
+        }
+    }
+
+    fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &[
+                "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else",
+                "enum", "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match",
+                "mod", "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct",
+                "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+            ],
+            Language::Python => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "try", "while", "with", "yield",
+            ],
+            Language::Java => &[
+                "abstract",
+                "boolean",
+                "break",
+                "byte",
+                "case",
+                "catch",
+                "char",
+                "class",
+                "continue",
+                "default",
+                "do",
+                "double",
+                "else",
+                "enum",
+                "extends",
+                "false",
+                "final",
+                "finally",
+                "float",
+                "for",
+                "if",
+                "implements",
+                "import",
+                "instanceof",
+                "int",
+                "interface",
+                "long",
+                "new",
+                "null",
+                "package",
+                "private",
+                "protected",
+                "public",
+                "return",
+                "short",
+                "static",
+                "super",
+                "switch",
+                "synchronized",
+                "this",
+                "throw",
+                "throws",
+                "true",
+                "try",
+                "void",
+                "volatile",
+                "while",
+            ],
+            Language::C => &[
+                "auto",
+                "bool",
+                "break",
+                "case",
+                "char",
+                "class",
+                "const",
+                "continue",
+                "default",
+                "delete",
+                "do",
+                "double",
+                "else",
+                "enum",
+                "extern",
+                "false",
+                "float",
+                "for",
+                "goto",
+                "if",
+                "inline",
+                "int",
+                "long",
+                "namespace",
+                "new",
+                "nullptr",
+                "private",
+                "protected",
+                "public",
+                "register",
+                "return",
+                "short",
+                "signed",
+                "sizeof",
+                "static",
+                "struct",
+                "switch",
+                "template",
+                "this",
+                "true",
+                "typedef",
+                "typename",
+                "union",
+                "unsigned",
+                "using",
+                "virtual",
+                "void",
+                "volatile",
+                "while",
+            ],
+            Language::JavaScript => &[
+                "async",
+                "await",
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "debugger",
+                "default",
+                "delete",
+                "do",
+                "else",
+                "enum",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "function",
+                "if",
+                "implements",
+                "import",
+                "in",
+                "instanceof",
+                "interface",
+                "let",
+                "new",
+                "null",
+                "of",
+                "return",
+                "super",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "type",
+                "typeof",
+                "var",
+                "void",
+                "while",
+                "with",
+                "yield",
+            ],
+        }
+    }
+}
+
+/// What a char of source code belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Class {
+    Code,
+    Comment,
+    Str,
+}
+
+/// Classify every char as code, comment or string literal
+fn classify(chars: &[char], lang: Language) -> Vec<Class> {
+    let mut classes = vec![Class::Code; chars.len()];
+    let at = |i: usize, s: &str| {
+        s.chars()
+            .enumerate()
+            .all(|(k, c)| chars.get(i + k) == Some(&c))
+    };
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        i = match lang {
+            Language::Python if c == '#' => {
+                let end = find(chars, i, |j| chars[j] == '\n');
+                mark(&mut classes, i, end, Class::Comment)
+            }
+            Language::Python if at(i, "\"\"\"") || at(i, "'''") => {
+                let quote: String = chars[i..i + 3].iter().collect();
+                let end = find(chars, i + 3, |j| at(j, &quote)) + 3;
+                mark(&mut classes, i, end, Class::Str)
+            }
+            Language::Python if c == '"' || c == '\'' => {
+                let end = skip_quoted(chars, i, c, true);
+                mark(&mut classes, i, end, Class::Str)
+            }
+            Language::Python => i + 1,
+            _ if at(i, "//") => {
+                let end = find(chars, i, |j| chars[j] == '\n');
+                mark(&mut classes, i, end, Class::Comment)
+            }
+            _ if at(i, "/*") => {
+                let end = find(chars, i + 2, |j| at(j, "*/")) + 2;
+                mark(&mut classes, i, end, Class::Comment)
+            }
+            Language::Rust if c == 'r' && raw_string_hashes(chars, i).is_some() => {
+                let hashes = raw_string_hashes(chars, i).unwrap_or(0);
+                let closing: String = std::iter::once('"')
+                    .chain("#".repeat(hashes).chars())
+                    .collect();
+                let end = find(chars, i + hashes + 2, |j| at(j, &closing)) + closing.len();
+                mark(&mut classes, i, end, Class::Str)
+            }
+            // 'a' and '\n' are chars, 'a without a closing quote is a lifetime
+            Language::Rust if c == '\'' => {
+                if chars.get(i + 1) == Some(&'\\') || chars.get(i + 2) == Some(&'\'') {
+                    let end = skip_quoted(chars, i, '\'', true);
+                    mark(&mut classes, i, end, Class::Str)
+                } else {
+                    i + 1
+                }
+            }
+            Language::JavaScript if c == '`' => {
+                let end = skip_quoted(chars, i, '`', false);
+                mark(&mut classes, i, end, Class::Str)
+            }
+            _ if c == '"' || c == '\'' => {
+                let end = skip_quoted(chars, i, c, true);
+                mark(&mut classes, i, end, Class::Str)
+            }
+            _ => i + 1,
+        };
+    }
+
+    classes
+}
+
+/// Marks chars[from..to] and returns where to continue
+fn mark(classes: &mut [Class], from: usize, to: usize, class: Class) -> usize {
+    let to = to.min(classes.len());
+    classes[from..to].fill(class);
+    to
+}
+
+/// First index from `start` where `pred` holds, or the end of the text
+fn find(chars: &[char], start: usize, pred: impl Fn(usize) -> bool) -> usize {
+    (start..chars.len())
+        .find(|&j| pred(j))
+        .unwrap_or(chars.len())
+}
+
+/// End (exclusive) of a quoted literal starting at `start`
+fn skip_quoted(chars: &[char], start: usize, quote: char, single_line: bool) -> usize {
+    let mut j = start + 1;
+    while j < chars.len() {
+        match chars[j] {
+            '\\' => j += 2,
+            c if c == quote => return j + 1,
+            '\n' if single_line => return j,
+            _ => j += 1,
+        }
+    }
+    chars.len()
+}
+
+/// Number of '#' in a Rust raw string opener (r"..." or r#"..."#) at `i`
+fn raw_string_hashes(chars: &[char], i: usize) -> Option<usize> {
+    if i > 0 && (chars[i - 1].is_alphanumeric() || chars[i - 1] == '_') {
+        return None;
+    }
+    let hashes = chars[i + 1..].iter().take_while(|&&c| c == '#').count();
+    (chars.get(i + 1 + hashes) == Some(&'"')).then_some(hashes)
+}
+
+/// Per-line facts needed to find item boundaries
+#[derive(Debug, Clone)]
+struct Line {
+    start: usize,
+    end: usize,
+    indent: usize,
+    blank: bool,
+    // Only comments, attributes or decorators on this line
+    preamble: bool,
+    // Starts inside a multi-line literal or open brackets
+    continued: bool,
+    // Brace depth after the line
+    depth_after: usize,
+    // Code with comments and literals blanked out
+    code: String,
+}
+
+fn scan_lines(chars: &[char], classes: &[Class], lang: Language) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut braces = 0usize;
+    let mut brackets = 0usize;
+    let mut start = 0;
+
+    while start <= chars.len() {
+        let end = find(chars, start, |j| chars[j] == '\n');
+        let code: String = (start..end)
+            .map(|j| {
+                if classes[j] == Class::Code {
+                    chars[j]
+                } else {
+                    ' '
+                }
+            })
+            .collect();
+        let text: String = chars[start..end].iter().collect();
+        let trimmed = code.trim();
+
+        let continued = (start > 0 && classes[start - 1] != Class::Code)
+            || (lang == Language::Python && brackets > 0);
+
+        for c in code.chars() {
+            match c {
+                '{' => braces += 1,
+                '}' => braces = braces.saturating_sub(1),
+                _ => {}
+            }
+            match c {
+                '(' | '[' | '{' => brackets += 1,
+                ')' | ']' | '}' => brackets = brackets.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        lines.push(Line {
+            start,
+            end,
+            indent: text
+                .chars()
+                .take_while(|c| c.is_whitespace())
+                .map(|c| if c == '\t' { 4 } else { 1 })
+                .sum(),
+            blank: text.trim().is_empty(),
+            preamble: !text.trim().is_empty()
+                && (trimmed.is_empty() || trimmed.starts_with("#[") || trimmed.starts_with('@')),
+            continued,
+            depth_after: braces,
+            code,
+        });
+
+        start = end + 1;
+    }
+
+    lines
+}
+
+/// Split a range of lines into items at the given nesting level
+fn split_items(
+    lines: &[Line],
+    range: Range<usize>,
+    level: usize,
+    lang: Language,
+) -> Vec<Range<usize>> {
+    let mut items = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for i in range.clone() {
+        let line = &lines[i];
+        if line.blank && start.is_none() {
+            continue;
+        }
+
+        if lang == Language::Python {
+            // A logical line at or left of the block indentation starts a new item
+            if let Some(s) = start
+                && !line.blank
+                && !line.continued
+                && line.indent <= level
+            {
+                items.push(s..i);
+                start = None;
+            }
+            start.get_or_insert(i);
+        } else {
+            let s = *start.get_or_insert(i);
+            // "fn foo()\n{" keeps the signature with its body
+            let brace_follows = lines[i + 1..range.end]
+                .iter()
+                .find(|l| !l.blank)
+                .is_some_and(|l| l.code.trim_start().starts_with('{'));
+            if line.depth_after <= level && !line.blank && !brace_follows {
+                items.push(s..i + 1);
+                start = None;
+            }
+        }
+    }
+
+    if let Some(s) = start {
+        items.push(s..range.end);
+    }
+
+    // Trailing blank lines are not part of an item
+    for item in &mut items {
+        while item.end > item.start + 1 && lines[item.end - 1].blank {
+            item.end -= 1;
+        }
+    }
+
+    items
+}
+
+static RUST_SYMBOLS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    symbol_patterns(&[
+        r"\bfn\s+([A-Za-z_]\w*)",
+        r"\b(?:struct|enum|trait|union|mod|type)\s+([A-Za-z_]\w*)",
+        r"\bmacro_rules!\s*([A-Za-z_]\w*)",
+        r"^\s*(?:unsafe\s+)?(impl\b(?:\s*<[^{]*?>)?\s+[^{]+?)\s*(?:where\b.*)?\{?\s*$",
+    ])
+});
+
+static PYTHON_SYMBOLS: LazyLock<Vec<Regex>> =
+    LazyLock::new(|| symbol_patterns(&[r"^\s*(?:async\s+)?(?:def|class)\s+([A-Za-z_]\w*)"]));
+
+static JAVA_SYMBOLS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    symbol_patterns(&[
+        r"\b(?:class|interface|enum|record)\s+([A-Za-z_]\w*)",
+        r"^[^=;]*?\b([A-Za-z_]\w*)\s*\([^;]*$",
+    ])
+});
+
+static C_SYMBOLS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    symbol_patterns(&[
+        r"\b(?:struct|class|enum|union|namespace)\s+([A-Za-z_]\w*)\s*\{?\s*$",
+        r"^[^=;]*?\b([A-Za-z_][\w:~]*)\s*\([^;]*$",
+    ])
+});
+
+static JS_SYMBOLS: LazyLock<Vec<Regex>> = LazyLock::new(|| {
+    symbol_patterns(&[
+        r"\bfunction\b\s*\*?\s*([A-Za-z_$][\w$]*)",
+        r"\b(?:class|interface|enum|namespace)\s+([A-Za-z_$][\w$]*)",
+        r"\b(?:const|let|var)\s+([A-Za-z_$][\w$]*)\s*=\s*(?:async\s+)?(?:function\b|\([^)]*\)\s*=>|[A-Za-z_$][\w$]*\s*=>)",
+        r"^\s*(?:(?:public|private|protected|static|async|get|set|readonly)\s+)*([A-Za-z_$][\w$]*)\s*\([^;]*\)\s*(?::[^={]+)?\{\s*$",
+    ])
+});
+
+fn symbol_patterns(patterns: &[&str]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .map(|p| Regex::new(p).expect("invalid symbol pattern"))
+        .collect()
+}
+
+/// Control flow that looks like a call followed by a block
+const NOT_SYMBOLS: &[&str] = &[
+    "if", "for", "while", "switch", "catch", "return", "sizeof", "else", "do", "try", "new",
+];
+
+/// Name of the item declared on a line, if any
+fn symbol_name(code: &str, lang: Language) -> Option<String> {
+    let patterns = match lang {
+        Language::Rust => &*RUST_SYMBOLS,
+        Language::Python => &*PYTHON_SYMBOLS,
+        Language::Java => &*JAVA_SYMBOLS,
+        Language::C => &*C_SYMBOLS,
+        Language::JavaScript => &*JS_SYMBOLS,
+    };
+
+    patterns.iter().find_map(|p| {
+        let name = p.captures(code)?.get(1)?.as_str().trim();
+        let first_word = code.split_whitespace().next().unwrap_or_default();
+        (!NOT_SYMBOLS.contains(&name) && !NOT_SYMBOLS.contains(&first_word))
+            .then(|| name.split_whitespace().collect::<Vec<_>>().join(" "))
+    })
+}
+
+/// Symbol declared by an item: the first line that is not a comment or attribute
+fn item_symbol(lines: &[Line], item: &Range<usize>, lang: Language) -> Option<String> {
+    lines[item.clone()]
+        .iter()
+        .find(|l| !l.blank && !l.preamble)
+        .and_then(|l| symbol_name(&l.code, lang))
+}
+
+struct CodeChunker<'a> {
+    chars: &'a [char],
+    lines: Vec<Line>,
+    lang: Language,
+    window: &'a SlidingWindow,
+    chunks: Vec<Chunk>,
+}
+
+impl CodeChunker<'_> {
+    fn len(&self, lines: &Range<usize>) -> usize {
+        self.lines[lines.end - 1].end - self.lines[lines.start].start
+    }
+
+    fn push(&mut self, lines: Range<usize>, symbol: Option<String>) {
+        let (start, end) = (self.lines[lines.start].start, self.lines[lines.end - 1].end);
+        self.chunks.push(Chunk {
+            text: self.chars[start..end].iter().collect(),
+            start_char: start,
+            end_char: end,
+            symbol,
+            ..Default::default()
+        });
+    }
+
+    /// Fixed windows over an item that cannot be split any further
+    fn push_fixed(&mut self, lines: Range<usize>, symbol: Option<String>) {
+        let start = self.lines[lines.start].start;
+        let end = self.lines[lines.end - 1].end;
+        let text: String = self.chars[start..end].iter().collect();
+        for mut chunk in fixed_chunks(&text, self.window) {
+            chunk.start_char += start;
+            chunk.end_char += start;
+            chunk.symbol = symbol.clone();
+            self.chunks.push(chunk);
+        }
+    }
+
+    /// Group items into chunks: each named item starts a chunk (with the
+    /// comments and attributes right above it), unnamed ones are packed.
+    fn chunk_range(&mut self, range: Range<usize>, level: usize, parent: Option<&str>) {
+        let max = self.window.window_size.max(1);
+        let items = split_items(&self.lines, range.clone(), level, self.lang);
+
+        // Nothing left to split at this level
+        if items.len() <= 1 && level > 0 {
+            self.push_fixed(range, parent.map(str::to_string));
+            return;
+        }
+
+        let qualify = |name: String| match parent {
+            Some(p) => format!("{} > {}", p, name),
+            None => name,
+        };
+
+        // The block header ("impl Foo {", "class Foo:") is named by the parent
+        let header = range
+            .clone()
+            .find(|&i| !self.lines[i].blank && !self.lines[i].preamble);
+
+        // (lines, symbol, start of trailing preamble)
+        let mut current: Option<(Range<usize>, Option<String>, Option<usize>)> = None;
+
+        for item in items {
+            let preamble = self.lines[item.clone()]
+                .iter()
+                .all(|l| l.blank || l.preamble);
+            let symbol = if level > 0 && header.is_some_and(|h| item.contains(&h)) {
+                None
+            } else {
+                item_symbol(&self.lines, &item, self.lang).map(&qualify)
+            };
+
+            // A lone closing brace stays with whatever it closes
+            let closing = self.lines[item.clone()].iter().all(|l| {
+                let code = l.code.trim();
+                !code.is_empty()
+                    && code
+                        .chars()
+                        .all(|c| matches!(c, '}' | ')' | ']' | ';' | ','))
+            });
+            if closing
+                && let Some((lines, prev, _)) = &current
+                && self.len(&(lines.start..item.end)) <= max
+            {
+                current = Some((lines.start..item.end, prev.clone(), None));
+                continue;
+            }
+
+            if symbol.is_some() {
+                // Comments and attributes directly above belong to the new item
+                let start = match current.take() {
+                    Some((lines, _, Some(p))) if p == lines.start => lines.start,
+                    Some((lines, prev, Some(p))) => {
+                        self.flush(lines.start..p, prev, level, parent);
+                        p
+                    }
+                    Some((lines, prev, None)) => {
+                        self.flush(lines, prev, level, parent);
+                        item.start
+                    }
+                    None => item.start,
+                };
+                current = Some((start..item.end, symbol, None));
+                continue;
+            }
+
+            current = match current.take() {
+                Some((lines, prev, pre))
+                    if prev.is_none() && self.len(&(lines.start..item.end)) <= max =>
+                {
+                    let pre = if preamble {
+                        pre.or(Some(item.start))
+                    } else {
+                        None
+                    };
+                    Some((lines.start..item.end, prev, pre))
+                }
+                Some((lines, prev, _)) => {
+                    self.flush(lines, prev, level, parent);
+                    Some((item.clone(), None, preamble.then_some(item.start)))
+                }
+                None => Some((item.clone(), None, preamble.then_some(item.start))),
+            };
+        }
+
+        if let Some((lines, symbol, _)) = current {
+            self.flush(lines, symbol, level, parent);
+        }
+    }
+
+    /// Emit a group of items, splitting it one level deeper if it is too large
+    fn flush(
+        &mut self,
+        lines: Range<usize>,
+        symbol: Option<String>,
+        level: usize,
+        parent: Option<&str>,
+    ) {
+        // Unnamed code inside an item belongs to that item
+        let symbol = symbol.or_else(|| parent.map(str::to_string));
+        if self.len(&lines) <= self.window.window_size.max(1) {
+            self.push(lines, symbol);
+        } else if level < MAX_NESTING {
+            let inner = match self.lang {
+                // Indentation of the block body
+                Language::Python => self.lines[lines.clone()]
+                    .iter()
+                    .filter(|l| !l.blank && !l.continued)
+                    .map(|l| l.indent)
+                    .find(|&indent| indent > level),
+                _ => Some(level + 1),
+            };
+            match inner {
+                Some(inner) => self.chunk_range(lines, inner, symbol.as_deref()),
+                None => self.push_fixed(lines, symbol),
+            }
+        } else {
+            self.push_fixed(lines, symbol);
+        }
+    }
+}
+
+/// Split source code into chunks along item boundaries
+pub fn code_chunks(content: &str, lang: Language, window: &SlidingWindow) -> Vec<Chunk> {
+    let chars: Vec<char> = content.chars().collect();
+    if chars.is_empty() {
+        return vec![];
+    }
+    let classes = classify(&chars, lang);
+    let lines = scan_lines(&chars, &classes, lang);
+    let line_count = lines.len();
+
+    let mut chunker = CodeChunker {
+        chars: &chars,
+        lines,
+        lang,
+        window,
+        chunks: Vec::new(),
+    };
+    chunker.chunk_range(0..line_count, 0, None);
+    chunker.chunks
+}
+
+/// Copy of the content keeping only the chars in scope (others become spaces,
+/// newlines are kept). Char positions are unchanged.
+pub fn scope_mask(content: &str, lang: Language, scope: CodeScope) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let classes = classify(&chars, lang);
+    let mut keep = vec![false; chars.len()];
+
+    match scope {
+        CodeScope::All => keep.fill(true),
+        CodeScope::Comments => {
+            for (k, class) in keep.iter_mut().zip(&classes) {
+                *k = *class == Class::Comment;
+            }
+        }
+        CodeScope::Strings => {
+            for (k, class) in keep.iter_mut().zip(&classes) {
+                *k = *class == Class::Str;
+            }
+        }
+        CodeScope::Identifiers => {
+            let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '$';
+            let mut i = 0;
+            while i < chars.len() {
+                if classes[i] != Class::Code || !is_ident(chars[i]) {
+                    i += 1;
+                    continue;
+                }
+                let end = find(&chars, i, |j| {
+                    classes[j] != Class::Code || !is_ident(chars[j])
+                });
+                let word: String = chars[i..end].iter().collect();
+                if !chars[i].is_numeric() && !lang.keywords().contains(&word.as_str()) {
+                    keep[i..end].fill(true);
+                }
+                i = end;
+            }
+        }
+    }
+
+    chars
+        .iter()
+        .zip(keep)
+        .map(|(&c, k)| if k || c == '\n' { c } else { ' ' })
+        .collect()
+}
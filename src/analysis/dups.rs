@@ -0,0 +1,239 @@
+// Near-duplicate detection across the walked corpus
+//
+// Each file is reduced to a set of word shingles taken from its text, the
+// set is summarized with a MinHash signature and locality sensitive hashing
+// (banding) proposes candidate pairs. Candidates above the threshold are
+// merged into clusters with a union-find.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use super::{bm25::tokenize, catch_file_panic, read_content, with_thread_pool};
+use crate::{config::Config, errors::ScoreError};
+
+/// Number of words per shingle
+const SHINGLE_SIZE: usize = 5;
+/// Number of hash functions in a signature
+const NUM_HASHES: usize = 128;
+/// LSH bands, NUM_HASHES must be divisible by this
+const NUM_BANDS: usize = 32;
+const ROWS_PER_BAND: usize = NUM_HASHES / NUM_BANDS;
+
+/// Two files with their estimated Jaccard similarity
+#[derive(Debug, Clone)]
+pub struct DuplicatePair {
+    pub first: PathBuf,
+    pub second: PathBuf,
+    pub similarity: f64,
+}
+
+/// A group of files that are near-duplicates of each other
+#[derive(Debug, Clone)]
+pub struct DuplicateCluster {
+    pub files: Vec<PathBuf>,
+    // Every pair within the cluster, most similar first
+    pub pairs: Vec<DuplicatePair>,
+}
+
+/// Clusters found plus the files that could not be read
+#[derive(Debug)]
+pub struct DuplicateReport {
+    pub clusters: Vec<DuplicateCluster>,
+    pub failures: Vec<(PathBuf, ScoreError)>,
+}
+
+/// MinHash signature of a file's shingle set
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature(Vec<u64>);
+
+impl Signature {
+    /// Compute the signature of a set of shingle hashes
+    pub fn from_shingles(shingles: &HashSet<u64>) -> Self {
+        let mut mins = vec![u64::MAX; NUM_HASHES];
+        for &shingle in shingles {
+            for (seed, min) in mins.iter_mut().enumerate() {
+                let h = mix(shingle ^ mix(seed as u64 + 1));
+                if h < *min {
+                    *min = h;
+                }
+            }
+        }
+        Self(mins)
+    }
+
+    /// Estimated Jaccard similarity (fraction of equal minimums)
+    pub fn similarity(&self, other: &Signature) -> f64 {
+        let equal = self.0.iter().zip(&other.0).filter(|(a, b)| a == b).count();
+        equal as f64 / NUM_HASHES as f64
+    }
+
+    fn band(&self, band: usize) -> &[u64] {
+        &self.0[band * ROWS_PER_BAND..(band + 1) * ROWS_PER_BAND]
+    }
+}
+
+/// SplitMix64 finalizer, cheap and well distributed
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+/// FNV-1a, stable across runs and platforms unlike the std hasher
+fn hash_words(words: &[String]) -> u64 {
+    let mut h: u64 = 0xcbf2_9ce4_8422_2325;
+    for word in words {
+        for b in word.bytes().chain(std::iter::once(b' ')) {
+            h ^= b as u64;
+            h = h.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    h
+}
+
+/// Word shingles of a whole text
+pub fn shingles(text: &str) -> HashSet<u64> {
+    let words: Vec<String> = tokenize(text).into_iter().map(|t| t.term).collect();
+    if words.is_empty() {
+        return HashSet::new();
+    }
+    // Short texts are a single shingle
+    if words.len() < SHINGLE_SIZE {
+        return HashSet::from([hash_words(&words)]);
+    }
+    words.windows(SHINGLE_SIZE).map(hash_words).collect()
+}
+
+/// Signature of a single file (PDF text goes through the usual extraction).
+/// Read whole, so chunking and code scope don't change what is compared
+fn file_signature(file: &Path) -> Result<Option<Signature>, ScoreError> {
+    let shingles = shingles(&read_content(file)?);
+
+    // Empty files carry no content to compare
+    if shingles.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(Signature::from_shingles(&shingles)))
+}
+
+/// Cluster files into near-duplicate groups.
+///
+/// Files whose estimated similarity reaches `config.threshold` end up in the
+/// same cluster. Only clusters with at least two files are returned, largest first.
+pub fn find_duplicates(files: &[PathBuf], config: &Config) -> Result<DuplicateReport, ScoreError> {
+    let outcomes: Vec<(&PathBuf, Result<Option<Signature>, ScoreError>)> =
+        with_thread_pool(config, || {
+            files
+                .par_iter()
+                .with_min_len(2)
+                .map(|f| (f, catch_file_panic(f, || file_signature(f))))
+                .collect()
+        });
+
+    let mut signed: Vec<(PathBuf, Signature)> = Vec::new();
+    let mut failures = Vec::new();
+    for (file, outcome) in outcomes {
+        match outcome {
+            Ok(Some(signature)) => signed.push((file.clone(), signature)),
+            Ok(None) => {}
+            Err(e) => failures.push((file.clone(), e)),
+        }
+    }
+
+    // LSH: files sharing any band bucket become candidate pairs
+    let mut candidates: HashSet<(usize, usize)> = HashSet::new();
+    for band in 0..NUM_BANDS {
+        let mut buckets: HashMap<&[u64], Vec<usize>> = HashMap::new();
+        for (i, (_, signature)) in signed.iter().enumerate() {
+            buckets.entry(signature.band(band)).or_default().push(i);
+        }
+        for bucket in buckets.values().filter(|b| b.len() > 1) {
+            for (n, &a) in bucket.iter().enumerate() {
+                for &b in &bucket[n + 1..] {
+                    candidates.insert((a, b));
+                }
+            }
+        }
+    }
+
+    let mut sets = UnionFind::new(signed.len());
+    for &(a, b) in &candidates {
+        if signed[a].1.similarity(&signed[b].1) >= config.threshold {
+            sets.union(a, b);
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..signed.len() {
+        groups.entry(sets.find(i)).or_default().push(i);
+    }
+
+    let mut clusters: Vec<DuplicateCluster> = groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| {
+            let mut pairs = Vec::new();
+            for (n, &a) in members.iter().enumerate() {
+                for &b in &members[n + 1..] {
+                    pairs.push(DuplicatePair {
+                        first: signed[a].0.clone(),
+                        second: signed[b].0.clone(),
+                        similarity: signed[a].1.similarity(&signed[b].1),
+                    });
+                }
+            }
+            pairs.sort_by(|a, b| {
+                b.similarity
+                    .partial_cmp(&a.similarity)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
+
+            let mut files: Vec<PathBuf> = members.iter().map(|&i| signed[i].0.clone()).collect();
+            files.sort();
+
+            DuplicateCluster { files, pairs }
+        })
+        .collect();
+
+    clusters.sort_by(|a, b| {
+        b.files
+            .len()
+            .cmp(&a.files.len())
+            .then_with(|| a.files.cmp(&b.files))
+    });
+
+    Ok(DuplicateReport { clusters, failures })
+}
+
+/// Minimal union-find with path halving
+struct UnionFind {
+    parent: Vec<usize>,
+}
+
+impl UnionFind {
+    fn new(n: usize) -> Self {
+        Self {
+            parent: (0..n).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parent[i] != i {
+            self.parent[i] = self.parent[self.parent[i]];
+            i = self.parent[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (ra, rb) = (self.find(a), self.find(b));
+        if ra != rb {
+            self.parent[rb] = ra;
+        }
+    }
+}
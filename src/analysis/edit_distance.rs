@@ -0,0 +1,100 @@
+// Word-level edit distance matching
+//
+// The query is split into words and slid over every run of the same number
+// of consecutive words in a chunk. Each word pair is compared with the
+// Damerau-Levenshtein distance (optimal string alignment variant), so typos
+// and transpositions like "recieve" -> "receive" still match closely while
+// scattered letters across a chunk never do.
+
+use super::bm25::{Token, tokenize};
+
+/// Optimal string alignment distance: insertions, deletions, substitutions
+/// and transpositions of adjacent characters all cost 1.
+pub fn damerau_levenshtein(a: &[char], b: &[char]) -> usize {
+    let (n, m) = (a.len(), b.len());
+    if n == 0 {
+        return m;
+    }
+    if m == 0 {
+        return n;
+    }
+
+    // Three rolling rows: i - 2, i - 1 and i
+    let mut before_prev: Vec<usize> = vec![0; m + 1];
+    let mut prev: Vec<usize> = (0..=m).collect();
+    let mut curr: Vec<usize> = vec![0; m + 1];
+
+    for i in 1..=n {
+        curr[0] = i;
+        for j in 1..=m {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            curr[j] = (prev[j] + 1).min(curr[j - 1] + 1).min(prev[j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                curr[j] = curr[j].min(before_prev[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut before_prev, &mut prev);
+        std::mem::swap(&mut prev, &mut curr);
+    }
+
+    prev[m]
+}
+
+/// Similarity of two words in [0, 1], 1.0 meaning identical
+pub fn word_similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - damerau_levenshtein(&a, &b) as f64 / longest as f64
+}
+
+/// Lowercased query words, prepared once per file
+pub fn query_words(query: &str) -> Vec<String> {
+    tokenize(query).into_iter().map(|t| t.term).collect()
+}
+
+/// Find the run of chunk words closest to the query words.
+///
+/// Returns the mean word similarity of the best window and the character
+/// indices of the words in it that resemble their query word at all.
+pub fn score_words(words: &[String], text: &str) -> (f64, Option<Vec<usize>>) {
+    let tokens = tokenize(text);
+    if words.is_empty() || tokens.is_empty() {
+        return (0.0, None);
+    }
+
+    // Chunks shorter than the query are compared against all their words
+    let width = words.len().min(tokens.len());
+
+    let mut best: Option<(f64, &[Token], Vec<f64>)> = None;
+    for window in tokens.windows(width) {
+        let similarities: Vec<f64> = words
+            .iter()
+            .zip(window)
+            .map(|(word, token)| word_similarity(word, &token.term))
+            .collect();
+        // Missing words (query longer than chunk) count as 0
+        let score = similarities.iter().sum::<f64>() / words.len() as f64;
+
+        if best.as_ref().is_none_or(|(s, _, _)| score > *s) {
+            best = Some((score, window, similarities));
+        }
+    }
+
+    match best {
+        Some((score, window, similarities)) if score > 0.0 => {
+            let indices: Vec<usize> = window
+                .iter()
+                .zip(similarities)
+                .filter(|(_, similarity)| *similarity > 0.0)
+                .flat_map(|(token, _)| token.start..token.start + token.len)
+                .collect();
+            (score, Some(indices))
+        }
+        _ => (0.0, None),
+    }
+}
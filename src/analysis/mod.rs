@@ -9,110 +9,521 @@ use std::{
 
 use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
 use rayon::prelude::*;
+use regex::Regex;
 
 use crate::{
-    config::{ALLOWED_BINARY_FILE_EXTS, Config, SimilarityAlgorithm},
-    errors::{ChunkError, ScoreError},
+    config::{
+        ALLOWED_BINARY_FILE_EXTS, ChunkingStrategy, CodeScope, Config, ScoreAggregation,
+        SimilarityAlgorithm,
+    },
+    errors::{ChunkError, IndexError, ScoreError},
+    index::{Index, IndexStats},
 };
 
+pub mod bm25;
+pub mod cancel;
+pub mod chunking;
+pub mod code;
+pub mod dups;
+pub mod edit_distance;
+pub mod progress;
+pub mod query;
+pub mod similar;
+
+use bm25::{Bm25Stats, FileTermStats};
+pub use cancel::CancellationToken;
+pub use progress::Progress;
+use progress::ProgressTracker;
+use query::{QueryExpr, TermScore};
+use similar::{ReferenceDocument, TermVector};
+
 // Return a score for each file
 // Needs a weighting function for multiple matches within a file
-pub fn analyse_files(files: &Vec<PathBuf>, config: &Config) -> Result<Vec<FileScore>, ScoreError> {
-    let results: Vec<Result<FileScore, ScoreError>> = files
+pub fn analyse_files(files: &[PathBuf], config: &Config) -> Result<AnalysisReport, ScoreError> {
+    analyse_files_with(files, config, &AnalysisHooks::default())
+}
+
+/// Scored files and the files that had to be skipped, with the reason
+#[derive(Debug, Default)]
+pub struct AnalysisReport {
+    pub scores: Vec<FileScore>,
+    pub failures: Vec<(PathBuf, ScoreError)>,
+    // Outcome of updating `Config::index_file`, `None` without an index
+    pub index: Option<Result<IndexStats, IndexError>>,
+}
+
+impl AnalysisReport {
+    /// Skipped files with printable reasons (errors themselves can't be cloned)
+    pub fn skipped(&self) -> Vec<SkippedFile> {
+        self.failures
+            .iter()
+            .map(|(path, e)| SkippedFile {
+                path: path.clone(),
+                reason: e.reason(),
+                message: e.to_string(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    // Short category, see `ScoreError::reason`
+    pub reason: &'static str,
+    pub message: String,
+}
+
+/// Result of one file, keyed by its path so failures can be reported
+type FileResult<'a> = (&'a Path, Result<FileScore, ScoreError>);
+
+/// Optional ways to follow and stop a running analysis, see `analyse_files_with`
+#[derive(Default)]
+pub struct AnalysisHooks<'a> {
+    // Stops the analysis, files not started yet are skipped
+    pub cancel: CancellationToken,
+    // Throttled progress (at most every 100ms, plus a final report)
+    pub on_progress: Option<&'a (dyn Fn(&Progress) + Sync)>,
+    // Each file as soon as it is scored, before the threshold is applied
+    pub on_result: Option<&'a (dyn Fn(&FileScore) + Sync)>,
+}
+
+/// Like `analyse_files`, with progress, streamed results and cancellation.
+///
+/// Callbacks run on the worker threads. Files not scored before cancelling are
+/// left out, so the returned scores are partial if `hooks.cancel.is_cancelled()`
+/// afterwards.
+pub fn analyse_files_with(
+    files: &[PathBuf],
+    config: &Config,
+    hooks: &AnalysisHooks,
+) -> Result<AnalysisReport, ScoreError> {
+    let cancel = &hooks.cancel;
+    // An index that can't be read is reported, the analysis goes on without it
+    let (index, index_error) = match config.index_file.as_deref().map(Index::open) {
+        Some(Ok(index)) => (Some(index), None),
+        Some(Err(e)) => (None, Some(e)),
+        None => (None, None),
+    };
+    let run = AnalysisRun {
+        hooks,
+        progress: ProgressTracker::new(files, hooks.on_progress),
+        index,
+    };
+
+    let results: Vec<FileResult> = with_thread_pool(config, || {
+        if let Some(reference) = &config.reference_file {
+            // Query by example: compare whole documents instead of matching a query
+            analyse_files_like(reference, files, config, &run)
+        } else {
+            Ok(match config.algorithm {
+                // BM25 needs corpus-wide statistics before any chunk can be scored
+                SimilarityAlgorithm::Bm25 => analyse_files_bm25(files, config, &run),
+                _ => files
+                    .par_iter()
+                    .with_min_len(2)
+                    .map(|f| {
+                        let result = run.file(f, || {
+                            score_file_in_pool(f, config, cancel, run.index.as_ref())
+                        });
+                        (f.as_path(), run.scored(result))
+                    })
+                    .collect(),
+            })
+        }
+    })?;
+    run.progress.finish();
+
+    // Keep going past unreadable files, callers decide how to report them
+    let mut report = AnalysisReport::default();
+    for (file, result) in results {
+        match result {
+            Ok(score) => report.scores.push(score),
+            // Not an error, the file was never looked at
+            Err(ScoreError::Cancelled) => {}
+            Err(e) => report.failures.push((file.to_path_buf(), e)),
+        }
+    }
+    // Also after a cancel, the files read so far are up to date
+    report.index = match run.index {
+        Some(index) => Some(index.save()),
+        None => index_error.map(Err),
+    };
+
+    Ok(report)
+}
+
+/// Run `work` on a Rayon pool with `config.num_threads` threads (0 means all).
+///
+/// Files and their chunks are both scored with `par_iter`. Nested parallel
+/// iterators run on the pool they are called from, so the whole analysis never
+/// uses more than `num_threads` threads. Calls from inside a pool (e.g.
+/// `score_file` during `analyse_files`) reuse it instead of building a new one.
+pub fn with_thread_pool<T: Send>(config: &Config, work: impl FnOnce() -> T + Send) -> T {
+    if rayon::current_thread_index().is_some() {
+        return work();
+    }
+
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(config.num_threads)
+        .thread_name(|i| format!("doc-simfinder-{}", i))
+        .build()
+    {
+        Ok(pool) => pool.install(work),
+        // Could not spawn threads, fall back to the caller's thread (and global pool)
+        Err(_) => work(),
+    }
+}
+
+/// Hooks, progress and index shared by all files of one analysis
+struct AnalysisRun<'a> {
+    hooks: &'a AnalysisHooks<'a>,
+    progress: ProgressTracker<'a>,
+    index: Option<Index>,
+}
+
+impl AnalysisRun<'_> {
+    /// Process one file: skipped once cancelled, panics turned into errors,
+    /// counted as done either way
+    fn file<T>(
+        &self,
+        file: &Path,
+        work: impl FnOnce() -> Result<T, ScoreError>,
+    ) -> Result<T, ScoreError> {
+        check_cancelled(&self.hooks.cancel)?;
+        let result = catch_file_panic(file, work);
+        self.progress.file_done(file);
+        result
+    }
+
+    /// Stream a file score to `on_result` as soon as it is ready
+    fn scored(&self, result: Result<FileScore, ScoreError>) -> Result<FileScore, ScoreError> {
+        if let (Ok(score), Some(on_result)) = (&result, self.hooks.on_result) {
+            on_result(score);
+        }
+        result
+    }
+}
+
+/// Two-pass BM25 analysis: chunk every file and gather term statistics,
+/// then score each file's chunks against the merged corpus statistics.
+fn analyse_files_bm25<'a>(
+    files: &'a [PathBuf],
+    config: &Config,
+    run: &'a AnalysisRun,
+) -> Vec<FileResult<'a>> {
+    let sliding_window = calculate_sliding_window(config.query.len(), config);
+
+    // Reading the files is the slow part, progress is reported for this pass
+    let chunked: Vec<_> = files
         .par_iter()
         .with_min_len(2)
         .map(|f| {
-            // Wrap each file processing in catch_unwind to handle panics
-            // For some reason pdf_extract can panic on corrupted PDFs
-            match std::panic::catch_unwind(AssertUnwindSafe(|| score_file(f, config))) {
-                Ok(result) => result,
-                Err(_) => Err(ScoreError::ChunkError(ChunkError::PdfProcessing(format!(
-                    "Processing panicked for file: {}",
-                    f.display()
-                )))),
-            }
+            let start_time = Instant::now();
+            let chunks = run.file(f, || {
+                Ok(load_chunks(f, &sliding_window, run.index.as_ref())?)
+            });
+            (f, start_time, chunks)
         })
         .collect();
 
-    // Filter out errors but log them
-    let successful_results: Vec<FileScore> = results
-        .into_iter()
-        .filter_map(|result| {
-            match result {
-                Ok(score) => Some(score),
-                Err(e) => {
-                    // Log the error but continue processing other files
-                    eprintln!("Warning: Skipping file - {}", e);
-                    None
-                }
-            }
+    let mut stats = Bm25Stats::new(&config.query);
+    for (chunks, terms) in chunked.iter().filter_map(|(_, _, c)| c.as_ref().ok()) {
+        match terms {
+            // Indexed files come with their term statistics
+            Some(terms) => stats.add_file(terms),
+            None => chunks.iter().for_each(|chunk| stats.add_chunk(chunk)),
+        }
+    }
+
+    chunked
+        .into_par_iter()
+        .with_min_len(2)
+        .map(|(f, start_time, chunks)| {
+            let result = check_cancelled(&run.hooks.cancel)
+                .and(chunks)
+                .map(|(chunks, _)| score_chunks(f, chunks, config, Some(&stats), start_time));
+            (f.as_path(), run.scored(result))
+        })
+        .collect()
+}
+
+/// Compare every file against a reference document.
+/// Fails as a whole if the reference itself cannot be read.
+fn analyse_files_like<'a>(
+    reference: &Path,
+    files: &'a [PathBuf],
+    config: &Config,
+    run: &AnalysisRun,
+) -> Result<Vec<FileResult<'a>>, ScoreError> {
+    let sliding_window = calculate_sliding_window(0, config);
+    let reference_doc = catch_file_panic(reference, || {
+        Ok(load_chunks(reference, &sliding_window, run.index.as_ref())?.0)
+    })
+    .map(ReferenceDocument::new)?;
+
+    // The reference is usually inside the search path, don't report it as its own match
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let reference_path = canonical(reference);
+
+    let mut results: Vec<FileResult> = files
+        .par_iter()
+        .with_min_len(2)
+        .filter(|f| canonical(f) != reference_path)
+        .map(|f| {
+            let result = run.file(f, || {
+                let start_time = Instant::now();
+                let (chunks, _) = load_chunks(f, &sliding_window, run.index.as_ref())?;
+                Ok(score_chunks_like(
+                    f,
+                    chunks,
+                    &reference_doc,
+                    config,
+                    start_time,
+                ))
+            });
+            (f.as_path(), run.scored(result))
         })
         .collect();
 
-    Ok(successful_results)
+    // Rank by overall document similarity (most similar first)
+    let score_of = |(_, r): &FileResult| r.as_ref().map(|s| s.score).unwrap_or(0.0);
+    results.sort_by(|a, b| {
+        score_of(b)
+            .partial_cmp(&score_of(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    Ok(results)
+}
+
+fn score_chunks_like(
+    file: &Path,
+    chunks: Vec<Chunk>,
+    reference: &ReferenceDocument,
+    config: &Config,
+    start_time: Instant,
+) -> FileScore {
+    let file_score = TermVector::from_chunks(&chunks).cosine(&reference.vector);
+    let mut top_chunks = rank_chunks(reference.pair_chunks(chunks), config);
+    top_chunks.truncate(config.top_n);
+
+    FileScore {
+        path: file.to_path_buf(),
+        score: file_score,
+        top_chunks,
+        analysis_duration: Some(start_time.elapsed()),
+    }
+}
+
+/// Fail with `ScoreError::Cancelled` once the analysis has been cancelled
+fn check_cancelled(cancel: &CancellationToken) -> Result<(), ScoreError> {
+    if cancel.is_cancelled() {
+        Err(ScoreError::Cancelled)
+    } else {
+        Ok(())
+    }
+}
+
+/// Run per-file work, turning a panic into a ScoreError
+fn catch_file_panic<T>(
+    file: &Path,
+    work: impl FnOnce() -> Result<T, ScoreError>,
+) -> Result<T, ScoreError> {
+    // Wrap each file processing in catch_unwind to handle panics
+    // For some reason pdf_extract can panic on corrupted PDFs
+    match std::panic::catch_unwind(AssertUnwindSafe(work)) {
+        Ok(result) => result,
+        Err(_) => Err(ScoreError::ChunkError(ChunkError::Panicked(format!(
+            "while processing {}",
+            file.display()
+        )))),
+    }
 }
 
 // Stream with BufReader
 // Create set of chunks
 // Run algo on chunks using rayon
 pub fn score_file(file: &Path, config: &Config) -> Result<FileScore, ScoreError> {
+    with_thread_pool(config, || {
+        score_file_in_pool(file, config, &CancellationToken::new(), None)
+    })
+}
+
+fn score_file_in_pool(
+    file: &Path,
+    config: &Config,
+    cancel: &CancellationToken,
+    index: Option<&Index>,
+) -> Result<FileScore, ScoreError> {
+    check_cancelled(cancel)?;
     let start_time = Instant::now();
-    let query = &config.query;
-    let sliding_window = calculate_sliding_window(query.len(), config);
+    let sliding_window = calculate_sliding_window(config.query.len(), config);
+    let (chunks, _) = load_chunks(file, &sliding_window, index)?; // Do better error handling here
+    // Reading can take a while (e.g. PDFs), don't start scoring after a cancel
+    check_cancelled(cancel)?;
+
+    // Scored on its own, the file itself is the BM25 corpus
+    let stats = match config.algorithm {
+        SimilarityAlgorithm::Bm25 => Some(Bm25Stats::from_chunks(&config.query, &chunks)),
+        _ => None,
+    };
 
-    let optimal_score =
-        calculate_approximate_optimal_score(query.len(), sliding_window.window_size);
-    let chunks = get_chunks(file, &sliding_window)?; // Do better error handling here
+    Ok(score_chunks(
+        file,
+        chunks,
+        config,
+        stats.as_ref(),
+        start_time,
+    ))
+}
 
-    let query_str: &str = query; // Coerce once
+/// Score, filter and rank the chunks of a single file
+fn score_chunks(
+    file: &Path,
+    chunks: Vec<Chunk>,
+    config: &Config,
+    stats: Option<&Bm25Stats>,
+    start_time: Instant,
+) -> FileScore {
+    let query_str: &str = &config.query; // Coerce once
+    let sliding_window = calculate_sliding_window(query_str.len(), config);
+    let optimal_score = calculate_optimal_score(
+        query_str,
+        sliding_window.window_size,
+        &config.algorithm,
+        stats,
+    );
+
+    let prepared = PreparedQuery::new(config, stats, sliding_window.window_size);
 
     // Parallelize using rayon
-    let mut scored_chunks: Vec<ScoredChunk> = chunks
-        .par_iter()
+    let scored_chunks: Vec<ScoredChunk> = chunks
+        .into_par_iter()
         .with_min_len(50)
         .map(|chunk| {
             // Normalize based on optimal score
-            let (raw_score, indices_opt) = score_chunk(query_str, &chunk, &config.algorithm);
-            let chunk_with_indices = chunk.clone();
+            let (raw_score, indices_opt) =
+                score_chunk(query_str, &chunk, &config.algorithm, &prepared);
+            let score = if optimal_score > 0.0 {
+                (raw_score / optimal_score).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
             ScoredChunk {
-                score: (raw_score / (optimal_score as f64)).clamp(0.0, 1.0),
-                chunk: chunk_with_indices,
+                score,
+                chunk,
                 indices: indices_opt,
+                reference_chunk: None,
+                term_scores: None,
             }
         })
         .collect();
 
-    // Sort by score
-    scored_chunks.sort_by(|a, b| {
-        b.score
-            .partial_cmp(&a.score)
-            .unwrap_or(std::cmp::Ordering::Equal)
-    });
-
-    // threshold is percentage of top chunks
-    scored_chunks.retain(|c| c.score >= config.threshold);
+    let total_chunks = scored_chunks.len();
+    let mut top_chunks = rank_chunks(scored_chunks, config);
+    let file_score = aggregate_file_score(&top_chunks, total_chunks, config);
+    top_chunks.truncate(config.top_n);
+
+    // Explain boolean scores term by term, only for the chunks that are reported
+    if let Some(expr) = &prepared.expr {
+        for c in top_chunks.iter_mut() {
+            c.term_scores =
+                query::evaluate(expr, c.chunk.search_text(), sliding_window.window_size)
+                    .map(|evaluation| evaluation.terms);
+        }
+    }
 
     // There might be no chunks above threshold
-    if scored_chunks.is_empty() {
-        return Ok(FileScore {
+    if top_chunks.is_empty() {
+        return FileScore {
             path: file.to_path_buf(),
             score: 0.0,
             top_chunks: vec![],
             analysis_duration: None,
-        });
+        };
     }
 
-    // Would be max score, can also use mean
-    let file_score = scored_chunks[0].score;
-
-    let top_chunks = scored_chunks.into_iter().take(config.top_n).collect();
-
-    Ok(FileScore {
+    FileScore {
         path: file.to_path_buf(),
         score: file_score,
         top_chunks,
         analysis_duration: Some(start_time.elapsed()),
-    })
+    }
+}
+
+/// Query state prepared once per file and shared by all of its chunks
+struct PreparedQuery<'a> {
+    bm25: Option<&'a Bm25Stats>,
+    expr: Option<QueryExpr>,
+    regex: Option<Regex>,
+    words: Vec<String>,
+    window_size: usize,
+}
+
+impl<'a> PreparedQuery<'a> {
+    // The query was already validated with the config, so parse errors can't happen here
+    fn new(config: &Config, bm25: Option<&'a Bm25Stats>, window_size: usize) -> Self {
+        Self {
+            bm25,
+            expr: match config.algorithm {
+                SimilarityAlgorithm::Boolean => query::parse_query(&config.query).ok(),
+                _ => None,
+            },
+            regex: match config.algorithm {
+                SimilarityAlgorithm::Regex => Regex::new(&config.query).ok(),
+                _ => None,
+            },
+            words: match config.algorithm {
+                SimilarityAlgorithm::EditDistance => edit_distance::query_words(&config.query),
+                _ => vec![],
+            },
+            window_size,
+        }
+    }
+}
+
+/// Sort chunks by score and drop those below the threshold
+fn rank_chunks(mut scored_chunks: Vec<ScoredChunk>, config: &Config) -> Vec<ScoredChunk> {
+    // Sort by score
+    scored_chunks.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    // threshold is percentage of top chunks
+    scored_chunks.retain(|c| c.score >= config.threshold);
+
+    scored_chunks
+}
+
+/// Combine the scores of a file's chunks into a single file score.
+///
+/// `ranked` holds the chunks above threshold (best first), `total_chunks`
+/// is the number of chunks the file was split into.
+fn aggregate_file_score(ranked: &[ScoredChunk], total_chunks: usize, config: &Config) -> f64 {
+    let Some(best) = ranked.first() else {
+        return 0.0;
+    };
+
+    match config.aggregation {
+        ScoreAggregation::Max => best.score,
+        // Missing slots count as 0, so one lucky chunk doesn't score like Max
+        ScoreAggregation::MeanTopK => {
+            let sum: f64 = ranked.iter().take(config.top_n).map(|c| c.score).sum();
+            sum / config.top_n.max(1) as f64
+        }
+        // Probabilistic OR: every extra match adds a share of what is left to 1.0
+        ScoreAggregation::DiminishingSum => {
+            1.0 - ranked.iter().map(|c| 1.0 - c.score).product::<f64>()
+        }
+        // A single lucky chunk in a long file counts half as much as a file matching throughout
+        ScoreAggregation::Coverage => {
+            let coverage = ranked.len() as f64 / total_chunks.max(1) as f64;
+            best.score * (0.5 + 0.5 * coverage)
+        }
+    }
 }
 
 /// Check if a file appears to be binary by reading the first few bytes
@@ -187,24 +598,106 @@ fn is_likely_binary(file: &Path) -> Result<bool, std::io::Error> {
 
 // We want some dynamic window sizing based on the query string.
 fn get_chunks(file: &Path, window: &SlidingWindow) -> Result<Vec<Chunk>, ChunkError> {
+    let content = read_content(file)?;
+    Ok(chunk_content(file, &content, window))
+}
+
+/// Chunks of a file, from the index when one is used. Term statistics only
+/// come with indexed chunks
+fn load_chunks(
+    file: &Path,
+    window: &SlidingWindow,
+    index: Option<&Index>,
+) -> Result<(Vec<Chunk>, Option<FileTermStats>), ChunkError> {
+    match index {
+        Some(index) => index
+            .chunks(file, window)
+            .map(|indexed| (indexed.chunks, Some(indexed.terms))),
+        None => Ok((get_chunks(file, window)?, None)),
+    }
+}
+
+/// Text content of a file: the extracted text of PDFs, UTF-8 text otherwise
+pub(crate) fn read_content(file: &Path) -> Result<String, ChunkError> {
     let file_ext = file.extension().unwrap_or_default().to_string_lossy();
     // Check if file is allowed and if not if it is likely binary before attempting to read as UTF-8
     if !ALLOWED_BINARY_FILE_EXTS.contains(&format!(".{}", &file_ext).as_str())
-        && is_likely_binary(&file)?
+        && is_likely_binary(file)?
     {
         return Err(ChunkError::BinaryFile(file.display().to_string()));
     }
 
     // TODO! I should refactor this
     // Quick implementation for project finishing
-    let content = match file_ext.as_ref() {
-        "pdf" => extract_pdf_text(file)?,
+    match file_ext.as_ref() {
+        "pdf" => extract_pdf_text(file),
         _ => {
             // Attempt to read file as UTF-8 text
-            read_text_file(file)?
+            read_text_file(file)
         }
+    }
+}
+
+/// Split the content of `file` into located chunks, following the window strategy
+pub(crate) fn chunk_content(file: &Path, content: &str, window: &SlidingWindow) -> Vec<Chunk> {
+    let file_ext = file.extension().unwrap_or_default().to_string_lossy();
+    let language = code::Language::from_extension(&file_ext);
+    let mut chunks = match window.strategy {
+        ChunkingStrategy::FixedWindow => fixed_chunks(content, window),
+        ChunkingStrategy::Sentence => chunking::sentence_chunks(content, window),
+        ChunkingStrategy::Structure => match (file_ext.as_ref(), language) {
+            ("md" | "markdown", _) => chunking::markdown_chunks(content, window),
+            (_, Some(language)) => code::code_chunks(content, language, window),
+            _ => chunking::sentence_chunks(content, window),
+        },
     };
 
+    if window.code_scope != CodeScope::All {
+        // Only source files have comments, strings and identifiers to search
+        let Some(language) = language else {
+            return vec![];
+        };
+        let masked: Vec<char> = code::scope_mask(content, language, window.code_scope)
+            .chars()
+            .collect();
+        for chunk in &mut chunks {
+            chunk.scoped_text = Some(masked[chunk.start_char..chunk.end_char].iter().collect());
+        }
+    }
+
+    locate_chunks(content, &mut chunks);
+
+    chunks
+}
+
+/// Fill in the byte offsets and the line/column of each chunk start.
+/// Chunkers only deal in char positions.
+fn locate_chunks(content: &str, chunks: &mut [Chunk]) {
+    let mut bytes: Vec<usize> = content.char_indices().map(|(b, _)| b).collect();
+    bytes.push(content.len());
+
+    // Char position of every line start
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(
+            content
+                .chars()
+                .enumerate()
+                .filter(|(_, c)| *c == '\n')
+                .map(|(i, _)| i + 1),
+        )
+        .collect();
+
+    for chunk in chunks {
+        chunk.start_byte = bytes[chunk.start_char];
+        chunk.end_byte = bytes[chunk.end_char];
+        chunk.line = line_starts.partition_point(|&s| s <= chunk.start_char);
+        chunk.column = chunk.start_char - line_starts[chunk.line - 1] + 1;
+        chunk.byte_column = chunk.start_byte - bytes[line_starts[chunk.line - 1]] + 1;
+    }
+}
+
+/// Cut text into windows of `window_size` chars overlapping by `overlap` chars
+fn fixed_chunks(content: &str, window: &SlidingWindow) -> Vec<Chunk> {
     // More efficient: work with char indices directly instead of collecting all chars
     let char_indices: Vec<(usize, char)> = content.char_indices().collect();
     let char_count = char_indices.len();
@@ -227,8 +720,9 @@ fn get_chunks(file: &Path, window: &SlidingWindow) -> Result<Vec<Chunk>, ChunkEr
 
         chunks.push(Chunk {
             text: chunk_text,
-            start_byte: start_idx,
-            end_byte: end_idx,
+            start_char: start_idx,
+            end_char: end_idx,
+            ..Default::default()
         });
 
         if end_idx == char_count {
@@ -238,7 +732,7 @@ fn get_chunks(file: &Path, window: &SlidingWindow) -> Result<Vec<Chunk>, ChunkEr
         start_idx = end_idx.saturating_sub(window.overlap);
     }
 
-    Ok(chunks)
+    chunks
 }
 
 /// Calculate a spread penalty based on how dispersed the match indices are.
@@ -283,11 +777,12 @@ fn score_chunk(
     query: &str,
     chunk: &Chunk,
     algo: &SimilarityAlgorithm,
+    prepared: &PreparedQuery,
 ) -> (f64, Option<Vec<usize>>) {
     match algo {
         SimilarityAlgorithm::Fuzzy => {
             let matcher = SkimMatcherV2::default();
-            match matcher.fuzzy_indices(&chunk.text, &query) {
+            match matcher.fuzzy_indices(chunk.search_text(), query) {
                 Some(res) => {
                     // Apply spread penalty: penalize if matched chars are far apart
                     let base_score = res.0 as f64;
@@ -298,8 +793,139 @@ fn score_chunk(
                 None => (0.0, None),
             }
         }
-        // TODO
-        SimilarityAlgorithm::LCS => (0.0, None),
+        SimilarityAlgorithm::LCS => match longest_common_substring(chunk.search_text(), query) {
+            Some(indices) => (indices.len() as f64, Some(indices)),
+            None => (0.0, None),
+        },
+        SimilarityAlgorithm::Bm25 => match prepared.bm25 {
+            Some(stats) => stats.score(chunk),
+            None => (0.0, None),
+        },
+        SimilarityAlgorithm::EditDistance => {
+            edit_distance::score_words(&prepared.words, chunk.search_text())
+        }
+        SimilarityAlgorithm::Regex => match &prepared.regex {
+            Some(regex) => score_regex(regex, chunk.search_text()),
+            None => (0.0, None),
+        },
+        SimilarityAlgorithm::Boolean => {
+            let evaluation = prepared
+                .expr
+                .as_ref()
+                .and_then(|e| query::evaluate(e, chunk.search_text(), prepared.window_size));
+            match evaluation {
+                Some(evaluation) if !evaluation.indices.is_empty() => {
+                    (evaluation.score, Some(evaluation.indices))
+                }
+                Some(evaluation) => (evaluation.score, None),
+                None => (0.0, None),
+            }
+        }
+    }
+}
+
+/// Find the longest common substring between a chunk and the query.
+///
+/// Comparison is case-insensitive. Returns the character indices of the
+/// matched run within `text` (same convention as `fuzzy_indices`), or None
+/// if no character is shared at all.
+///
+/// Uses the classic dynamic programming table, keeping only a single row
+/// (O(query_len) memory, O(text_len * query_len) time).
+fn longest_common_substring(text: &str, query: &str) -> Option<Vec<usize>> {
+    // Lowercase per character so indices stay aligned with the original text
+    let fold = |c: char| c.to_lowercase().next().unwrap_or(c);
+    let text_chars: Vec<char> = text.chars().map(fold).collect();
+    let query_chars: Vec<char> = query.chars().map(fold).collect();
+
+    if text_chars.is_empty() || query_chars.is_empty() {
+        return None;
+    }
+
+    // row[j] = length of common suffix of text[..i] and query[..j]
+    let mut row = vec![0usize; query_chars.len() + 1];
+    let mut best_len = 0;
+    let mut best_end = 0; // exclusive end index in text
+
+    for (i, &tc) in text_chars.iter().enumerate() {
+        // Iterate backwards so row[j - 1] still holds the previous row's value
+        for j in (1..=query_chars.len()).rev() {
+            if tc == query_chars[j - 1] {
+                row[j] = row[j - 1] + 1;
+                if row[j] > best_len {
+                    best_len = row[j];
+                    best_end = i + 1;
+                }
+            } else {
+                row[j] = 0;
+            }
+        }
+    }
+
+    if best_len == 0 {
+        return None;
+    }
+
+    Some((best_end - best_len..best_end).collect())
+}
+
+/// Score a chunk by its regex matches.
+///
+/// Any match scores at least 0.75 so single hits pass the default threshold,
+/// more matches and a larger share of matched text push the score towards 1.0:
+///   score = 1 - 0.25 * (1 - coverage) / match_count
+///
+/// Matched byte spans are converted to character indices for highlighting.
+fn score_regex(regex: &Regex, text: &str) -> (f64, Option<Vec<usize>>) {
+    let spans: Vec<(usize, usize)> = regex
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| (m.start(), m.end()))
+        .collect();
+
+    if spans.is_empty() {
+        return (0.0, None);
+    }
+
+    // Matches never overlap and come in order, so a single pass maps bytes to chars
+    let mut indices = Vec::new();
+    let mut span_iter = spans.iter().peekable();
+    for (char_idx, (byte_idx, _)) in text.char_indices().enumerate() {
+        while span_iter.peek().is_some_and(|&&(_, end)| byte_idx >= end) {
+            span_iter.next();
+        }
+        match span_iter.peek() {
+            Some(&&(start, _)) if byte_idx >= start => indices.push(char_idx),
+            Some(_) => {}
+            None => break,
+        }
+    }
+
+    let total_chars = text.chars().count().max(1);
+    let coverage = indices.len() as f64 / total_chars as f64;
+    let score = 1.0 - 0.25 * (1.0 - coverage) / spans.len() as f64;
+
+    (score, Some(indices))
+}
+
+/// Best achievable raw score for a query, used to normalize chunk scores to [0, 1].
+fn calculate_optimal_score(
+    query: &str,
+    window_size: usize,
+    algo: &SimilarityAlgorithm,
+    stats: Option<&Bm25Stats>,
+) -> f64 {
+    match algo {
+        SimilarityAlgorithm::Fuzzy => {
+            calculate_approximate_optimal_score(query.len(), window_size) as f64
+        }
+        // An exact occurrence of the whole query is the longest possible substring
+        SimilarityAlgorithm::LCS => query.chars().count() as f64,
+        SimilarityAlgorithm::Bm25 => stats.map(Bm25Stats::optimal_score).unwrap_or(0.0),
+        // Already normalized when scoring
+        SimilarityAlgorithm::Boolean
+        | SimilarityAlgorithm::Regex
+        | SimilarityAlgorithm::EditDistance => 1.0,
     }
 }
 
@@ -315,13 +941,9 @@ fn calculate_approximate_optimal_score(query_len: usize, window_size: usize) ->
     let mut s_contain_match = "1".repeat(window_size - query_len);
     s_contain_match.push_str(pattern);
 
-    match matcher.fuzzy_match(s_contain_match.as_str(), pattern) {
-        Some(score) => {
-            // println!("optimal score: {}", score);
-            score
-        }
-        None => 0,
-    }
+    matcher
+        .fuzzy_match(s_contain_match.as_str(), pattern)
+        .unwrap_or_default()
 }
 
 fn calculate_sliding_window(query_len: usize, config: &Config) -> SlidingWindow {
@@ -336,6 +958,8 @@ fn calculate_sliding_window(query_len: usize, config: &Config) -> SlidingWindow
     SlidingWindow {
         window_size: ws,
         overlap: ws / 10,
+        strategy: config.chunking,
+        code_scope: config.code_scope,
     }
 }
 
@@ -344,14 +968,11 @@ fn extract_pdf_text(file: &Path) -> Result<String, ChunkError> {
     use std::panic::{AssertUnwindSafe, catch_unwind};
 
     // Check file size before processing
-    let metadata = fs::metadata(file).map_err(|e| ChunkError::Io(e))?;
+    let metadata = fs::metadata(file).map_err(ChunkError::Io)?;
     
     const MAX_PDF_SIZE: u64 = 10 * 1024 * 1024; // 10 MB
     if metadata.len() > MAX_PDF_SIZE {
-        return Err(ChunkError::PdfProcessing(format!(
-            "PDF too large ({}MB > 10MB)",
-            metadata.len() / (1024 * 1024)
-        )));
+        return Err(ChunkError::PdfTooLarge(metadata.len() / (1024 * 1024)));
     }
 
     let file_path = file.to_path_buf();
@@ -362,8 +983,8 @@ fn extract_pdf_text(file: &Path) -> Result<String, ChunkError> {
     })) {
         Ok(Ok(text)) => Ok(text),
         Ok(Err(e)) => Err(e),
-        Err(_) => Err(ChunkError::PdfProcessing(
-            "PDF parser panicked (corrupted or unsupported format)".to_string()
+        Err(_) => Err(ChunkError::Panicked(
+            "PDF parser (corrupted or unsupported format)".to_string(),
         )),
     }
 }
@@ -413,15 +1034,71 @@ fn read_text_file(file: &Path) -> Result<String, ChunkError> {
 pub struct SlidingWindow {
     pub window_size: usize, // in characters
     pub overlap: usize,     // in characters
+    pub strategy: ChunkingStrategy,
+    pub code_scope: CodeScope,
 }
 
 // Think of tradeoffs, storing chunk data
 // or only references using start_byte, end_byte and read from it later.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Chunk {
     pub text: String,
+    // Position in the file content, in chars
+    pub start_char: usize,
+    pub end_char: usize,
+    // Position in the file content, in bytes
     pub start_byte: usize,
     pub end_byte: usize,
+    // 1-based line and column (in chars) of the first char
+    pub line: usize,
+    pub column: usize,
+    // 1-based column in bytes of the first char, as editors count it
+    pub byte_column: usize,
+    // Enclosing Markdown headings, e.g. "Setup > Prerequisites"
+    pub heading_path: Option<String>,
+    // Enclosing source code item, e.g. "impl Display for FileScore > fmt"
+    pub symbol: Option<String>,
+    // Text restricted to the configured code scope (other chars blanked)
+    pub scoped_text: Option<String>,
+}
+
+impl Chunk {
+    /// Text the query is matched against
+    pub fn search_text(&self) -> &str {
+        self.scoped_text.as_deref().unwrap_or(&self.text)
+    }
+
+    /// Heading path or code symbol the chunk belongs to
+    pub fn location(&self) -> Option<&str> {
+        self.heading_path.as_deref().or(self.symbol.as_deref())
+    }
+
+    /// 1-based line and column of a char index into `text`
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        let (mut line, mut column) = (self.line, self.column);
+        for c in self.text.chars().take(index) {
+            if c == '\n' {
+                line += 1;
+                column = 1;
+            } else {
+                column += 1;
+            }
+        }
+        (line, column)
+    }
+
+    /// 1-based byte column of a char index into `text`
+    pub fn byte_column(&self, index: usize) -> usize {
+        let at = self
+            .text
+            .char_indices()
+            .nth(index)
+            .map_or(self.text.len(), |(byte, _)| byte);
+        match self.text[..at].rfind('\n') {
+            Some(newline) => at - newline,
+            None => self.byte_column + at,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -429,6 +1106,25 @@ pub struct ScoredChunk {
     pub score: f64,
     pub indices: Option<Vec<usize>>,
     pub chunk: Chunk,
+    // Most similar chunk of the reference document (query by example only)
+    pub reference_chunk: Option<Chunk>,
+    // Per-term breakdown of the score (boolean queries only)
+    pub term_scores: Option<Vec<TermScore>>,
+}
+
+impl ScoredChunk {
+    /// Line and column of the first match, or of the chunk start without matches
+    pub fn position(&self) -> (usize, usize) {
+        let first = self.indices.as_ref().and_then(|i| i.iter().min().copied());
+        self.chunk.position(first.unwrap_or(0))
+    }
+
+    /// Like `position`, with the column in bytes
+    pub fn byte_position(&self) -> (usize, usize) {
+        let first = self.indices.as_ref().and_then(|i| i.iter().min().copied());
+        let index = first.unwrap_or(0);
+        (self.chunk.position(index).0, self.chunk.byte_column(index))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -439,6 +1135,14 @@ pub struct FileScore {
     pub analysis_duration: Option<std::time::Duration>,
 }
 
+impl FileScore {
+    /// Whether the file is reported as a match. Files with matching chunks are
+    /// kept even if the aggregated score (e.g. mean or coverage) is below threshold
+    pub fn is_match(&self, threshold: f64) -> bool {
+        !self.top_chunks.is_empty() || self.score >= threshold
+    }
+}
+
 impl Display for FileScore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "File \"{}\"", self.path.display())?;
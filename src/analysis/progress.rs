@@ -0,0 +1,137 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+/// Minimum time between two progress reports
+const REPORT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Snapshot of a running analysis, passed to the progress callback
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Progress {
+    pub files_done: usize,
+    pub total_files: usize,
+    pub bytes_done: u64,
+    pub total_bytes: u64,
+    // Last file that finished
+    pub current_file: Option<PathBuf>,
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Share of the work done [0..1], by bytes if sizes are known
+    pub fn ratio(&self) -> f64 {
+        if self.total_bytes > 0 {
+            (self.bytes_done as f64 / self.total_bytes as f64).min(1.0)
+        } else if self.total_files > 0 {
+            (self.files_done as f64 / self.total_files as f64).min(1.0)
+        } else {
+            0.0
+        }
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.bytes_done as f64, self.elapsed)
+    }
+
+    pub fn files_per_sec(&self) -> f64 {
+        per_sec(self.files_done as f64, self.elapsed)
+    }
+
+    /// Estimated time left, extrapolated from the throughput so far
+    pub fn eta(&self) -> Option<Duration> {
+        let ratio = self.ratio();
+        if ratio <= 0.0 {
+            return None;
+        }
+        Some(self.elapsed.mul_f64((1.0 - ratio) / ratio))
+    }
+}
+
+fn per_sec(amount: f64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 { amount / secs } else { 0.0 }
+}
+
+/// Counts finished files from the worker threads and reports throttled progress
+pub(crate) struct ProgressTracker<'a> {
+    total_files: usize,
+    total_bytes: u64,
+    files_done: AtomicUsize,
+    bytes_done: AtomicU64,
+    start: Instant,
+    // Time of the last report, also serializes calls to `on_progress`
+    last_report: Mutex<Option<Instant>>,
+    on_progress: Option<&'a (dyn Fn(&Progress) + Sync)>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        files: &[PathBuf],
+        on_progress: Option<&'a (dyn Fn(&Progress) + Sync)>,
+    ) -> Self {
+        Self {
+            total_files: files.len(),
+            // Nobody listens, don't stat every file
+            total_bytes: match on_progress {
+                Some(_) => files.iter().map(|f| file_size(f)).sum(),
+                None => 0,
+            },
+            files_done: AtomicUsize::new(0),
+            bytes_done: AtomicU64::new(0),
+            start: Instant::now(),
+            last_report: Mutex::new(None),
+            on_progress,
+        }
+    }
+
+    /// Record a finished (scored or failed) file
+    pub(crate) fn file_done(&self, file: &Path) {
+        if self.on_progress.is_none() {
+            return;
+        }
+        self.bytes_done
+            .fetch_add(file_size(file), Ordering::Relaxed);
+        self.files_done.fetch_add(1, Ordering::Relaxed);
+
+        // Another thread is reporting right now, the next file will catch up
+        let Ok(mut last_report) = self.last_report.try_lock() else {
+            return;
+        };
+        if last_report.is_some_and(|t| t.elapsed() < REPORT_INTERVAL) {
+            return;
+        }
+        *last_report = Some(Instant::now());
+        self.report(Some(file));
+    }
+
+    /// Always report the final counts, whatever the throttling skipped
+    pub(crate) fn finish(&self) {
+        let _last_report = self.last_report.lock().unwrap_or_else(|e| e.into_inner());
+        self.report(None);
+    }
+
+    // Called with `last_report` held, so reports never go backwards
+    fn report(&self, current_file: Option<&Path>) {
+        let Some(on_progress) = self.on_progress else {
+            return;
+        };
+        on_progress(&Progress {
+            files_done: self.files_done.load(Ordering::Relaxed),
+            total_files: self.total_files,
+            bytes_done: self.bytes_done.load(Ordering::Relaxed),
+            total_bytes: self.total_bytes,
+            current_file: current_file.map(Path::to_path_buf),
+            elapsed: self.start.elapsed(),
+        });
+    }
+}
+
+fn file_size(file: &Path) -> u64 {
+    fs::metadata(file).map(|m| m.len()).unwrap_or(0)
+}
@@ -0,0 +1,408 @@
+// Boolean query language
+//
+// Grammar (operators are case-sensitive, lowercase "and"/"or" are plain terms):
+//
+//   query   := or
+//   or      := and ("OR" and)*
+//   and     := unary (["AND"] unary)*        -- juxtaposition means AND
+//   unary   := ("NOT" | "-") unary | primary
+//   primary := "(" query ")" | "\"phrase\"" | term
+//
+// Example: "error handling" -panic OR (retry AND backoff)
+
+use fuzzy_matcher::{FuzzyMatcher, skim::SkimMatcherV2};
+
+use super::{calculate_approximate_optimal_score, calculate_spread_penalty};
+use crate::errors::QueryParseError;
+
+/// Parsed query
+#[derive(Debug, Clone, PartialEq)]
+pub enum QueryExpr {
+    /// Single word, matched exactly or fuzzily
+    Term(String),
+    /// Quoted phrase, matched exactly (case-insensitive)
+    Phrase(String),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+    Not(Box<QueryExpr>),
+}
+
+/// Contribution of a single term or phrase to a chunk score
+#[derive(Debug, Clone, PartialEq)]
+pub struct TermScore {
+    pub term: String,
+    pub score: f64,
+    // Term appears under NOT and was (necessarily) absent from the chunk
+    pub negated: bool,
+}
+
+impl std::fmt::Display for TermScore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negated {
+            write!(f, "-{}", self.term)
+        } else {
+            write!(f, "{} {:.2}", self.term, self.score)
+        }
+    }
+}
+
+/// Result of evaluating a query against one chunk
+#[derive(Debug, Clone)]
+pub struct Evaluation {
+    pub score: f64,
+    pub indices: Vec<usize>,
+    pub terms: Vec<TermScore>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Phrase(String),
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+}
+
+fn lex(input: &str) -> Result<Vec<Token>, QueryParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(&ch) = chars.peek() {
+        match ch {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push(Token::LParen);
+            }
+            ')' => {
+                chars.next();
+                tokens.push(Token::RParen);
+            }
+            '"' => {
+                chars.next();
+                let mut phrase = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some(c) => phrase.push(c),
+                        None => return Err(QueryParseError::UnterminatedQuote),
+                    }
+                }
+                if !phrase.trim().is_empty() {
+                    tokens.push(Token::Phrase(phrase.trim().to_string()));
+                }
+            }
+            // A leading '-' negates the following term, phrase or group
+            '-' => {
+                chars.next();
+                match chars.peek() {
+                    Some(c) if !c.is_whitespace() => tokens.push(Token::Not),
+                    _ => tokens.push(Token::Word("-".to_string())),
+                }
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || c == '(' || c == ')' || c == '"' {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => Token::Word(word),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn parse_or(&mut self) -> Result<QueryExpr, QueryParseError> {
+        let mut children = vec![self.parse_and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            children.push(self.parse_and()?);
+        }
+        Ok(flatten(children, QueryExpr::Or))
+    }
+
+    fn parse_and(&mut self) -> Result<QueryExpr, QueryParseError> {
+        let mut children = vec![self.parse_unary()?];
+        loop {
+            match self.peek() {
+                Some(Token::And) => {
+                    self.next();
+                    children.push(self.parse_unary()?);
+                }
+                Some(Token::Or) | Some(Token::RParen) | None => break,
+                Some(_) => children.push(self.parse_unary()?),
+            }
+        }
+        Ok(flatten(children, QueryExpr::And))
+    }
+
+    fn parse_unary(&mut self) -> Result<QueryExpr, QueryParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.next();
+            return Ok(QueryExpr::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<QueryExpr, QueryParseError> {
+        match self.next() {
+            Some(Token::Word(w)) => Ok(QueryExpr::Term(w)),
+            Some(Token::Phrase(p)) => Ok(QueryExpr::Phrase(p)),
+            Some(Token::LParen) => {
+                let expr = self.parse_or()?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err(QueryParseError::UnbalancedParenthesis),
+                }
+            }
+            Some(Token::RParen) => Err(QueryParseError::UnbalancedParenthesis),
+            Some(Token::And) => Err(QueryParseError::MissingOperand("AND".into())),
+            Some(Token::Or) => Err(QueryParseError::MissingOperand("OR".into())),
+            Some(Token::Not) | None => Err(QueryParseError::MissingOperand("NOT".into())),
+        }
+    }
+}
+
+fn flatten(mut children: Vec<QueryExpr>, combine: fn(Vec<QueryExpr>) -> QueryExpr) -> QueryExpr {
+    if children.len() == 1 {
+        children.remove(0)
+    } else {
+        combine(children)
+    }
+}
+
+/// Parse a query string into an expression tree
+pub fn parse_query(input: &str) -> Result<QueryExpr, QueryParseError> {
+    let tokens = lex(input)?;
+    if tokens.is_empty() {
+        return Err(QueryParseError::Empty);
+    }
+
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.parse_or()?;
+
+    if parser.pos < parser.tokens.len() {
+        return Err(QueryParseError::UnbalancedParenthesis);
+    }
+
+    Ok(expr)
+}
+
+/// Intermediate result while walking the tree
+struct Partial {
+    matched: bool,
+    // None for parts that constrain the match without scoring (negations)
+    score: Option<f64>,
+    indices: Vec<usize>,
+    terms: Vec<TermScore>,
+}
+
+fn fold(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// Start indices (in chars) of every occurrence of needle in haystack
+fn find_occurrences(haystack: &[char], needle: &[char]) -> Vec<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return vec![];
+    }
+    (0..=haystack.len() - needle.len())
+        .filter(|&i| haystack[i..i + needle.len()] == *needle)
+        .collect()
+}
+
+struct EvalContext<'a> {
+    text: &'a str,
+    folded: Vec<char>,
+    window_size: usize,
+}
+
+impl EvalContext<'_> {
+    fn exact(&self, literal: &str) -> Vec<usize> {
+        let needle: Vec<char> = literal.chars().map(fold).collect();
+        find_occurrences(&self.folded, &needle)
+            .into_iter()
+            .flat_map(|start| start..start + needle.len())
+            .collect()
+    }
+
+    fn occurs(&self, expr: &QueryExpr) -> bool {
+        match expr {
+            QueryExpr::Term(t) | QueryExpr::Phrase(t) => !self.exact(t).is_empty(),
+            QueryExpr::And(children) => children.iter().all(|c| self.occurs(c)),
+            QueryExpr::Or(children) => children.iter().any(|c| self.occurs(c)),
+            QueryExpr::Not(child) => !self.occurs(child),
+        }
+    }
+
+    fn eval(&self, expr: &QueryExpr) -> Partial {
+        match expr {
+            QueryExpr::Term(term) => {
+                // Exact occurrences win, otherwise fall back to a fuzzy match
+                let exact = self.exact(term);
+                let (score, indices) = if !exact.is_empty() {
+                    (1.0, exact)
+                } else {
+                    self.fuzzy(term)
+                };
+                Partial {
+                    matched: score > 0.0,
+                    score: Some(score),
+                    indices,
+                    terms: vec![TermScore {
+                        term: term.clone(),
+                        score,
+                        negated: false,
+                    }],
+                }
+            }
+            QueryExpr::Phrase(phrase) => {
+                let indices = self.exact(phrase);
+                let score = if indices.is_empty() { 0.0 } else { 1.0 };
+                Partial {
+                    matched: score > 0.0,
+                    score: Some(score),
+                    indices,
+                    terms: vec![TermScore {
+                        term: format!("\"{}\"", phrase),
+                        score,
+                        negated: false,
+                    }],
+                }
+            }
+            QueryExpr::And(children) => {
+                let parts: Vec<Partial> = children.iter().map(|c| self.eval(c)).collect();
+                let scores: Vec<f64> = parts.iter().filter_map(|p| p.score).collect();
+                let score = if scores.is_empty() {
+                    None
+                } else {
+                    Some(scores.iter().sum::<f64>() / scores.len() as f64)
+                };
+                combine(parts.iter().all(|p| p.matched), score, parts)
+            }
+            QueryExpr::Or(children) => {
+                let parts: Vec<Partial> = children.iter().map(|c| self.eval(c)).collect();
+                let best = parts
+                    .iter()
+                    .filter(|p| p.matched)
+                    .filter_map(|p| p.score)
+                    .fold(None, |acc: Option<f64>, s| {
+                        Some(acc.map_or(s, |a| a.max(s)))
+                    });
+                // Only matched alternatives contribute highlights
+                let parts = parts.into_iter().filter(|p| p.matched).collect::<Vec<_>>();
+                let matched = !parts.is_empty();
+                combine(matched, best, parts)
+            }
+            QueryExpr::Not(child) => Partial {
+                matched: !self.occurs(child),
+                score: None,
+                indices: vec![],
+                terms: literals(child)
+                    .into_iter()
+                    .map(|term| TermScore {
+                        term,
+                        score: 0.0,
+                        negated: true,
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    fn fuzzy(&self, term: &str) -> (f64, Vec<usize>) {
+        let matcher = SkimMatcherV2::default();
+        let optimal = calculate_approximate_optimal_score(term.len(), self.window_size) as f64;
+        match matcher.fuzzy_indices(self.text, term) {
+            Some((raw, indices)) if optimal > 0.0 => {
+                let penalty = calculate_spread_penalty(&indices, term.len());
+                (((raw as f64) * penalty / optimal).clamp(0.0, 1.0), indices)
+            }
+            _ => (0.0, vec![]),
+        }
+    }
+}
+
+fn combine(matched: bool, score: Option<f64>, parts: Vec<Partial>) -> Partial {
+    let mut indices = Vec::new();
+    let mut terms = Vec::new();
+    for part in parts {
+        indices.extend(part.indices);
+        terms.extend(part.terms);
+    }
+    Partial {
+        matched,
+        score,
+        indices,
+        terms,
+    }
+}
+
+fn literals(expr: &QueryExpr) -> Vec<String> {
+    match expr {
+        QueryExpr::Term(t) => vec![t.clone()],
+        QueryExpr::Phrase(p) => vec![format!("\"{}\"", p)],
+        QueryExpr::And(children) | QueryExpr::Or(children) => {
+            children.iter().flat_map(literals).collect()
+        }
+        QueryExpr::Not(child) => literals(child),
+    }
+}
+
+/// Evaluate a query against a chunk of text.
+///
+/// Returns None when the chunk does not satisfy the query (e.g. an excluded
+/// term occurs). The score is in [0, 1]: AND averages its scored operands,
+/// OR takes the best matching alternative, NOT only filters.
+pub fn evaluate(expr: &QueryExpr, text: &str, window_size: usize) -> Option<Evaluation> {
+    let ctx = EvalContext {
+        text,
+        folded: text.chars().map(fold).collect(),
+        window_size,
+    };
+
+    let result = ctx.eval(expr);
+    if !result.matched {
+        return None;
+    }
+
+    let mut indices = result.indices;
+    indices.sort_unstable();
+    indices.dedup();
+
+    Some(Evaluation {
+        score: result.score.unwrap_or(0.0),
+        indices,
+        terms: result.terms,
+    })
+}
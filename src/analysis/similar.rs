@@ -0,0 +1,121 @@
+// Document-to-document similarity ("query by example")
+//
+// The reference document and every candidate file are turned into term
+// frequency vectors and compared with cosine similarity. Chunks are compared
+// the same way to find the most similar passage pairs.
+
+use std::collections::HashMap;
+
+use rayon::prelude::*;
+
+use super::{Chunk, ScoredChunk, bm25::tokenize};
+
+/// L2-normalized term frequency vector
+#[derive(Debug, Clone, Default)]
+pub struct TermVector {
+    weights: HashMap<String, f64>,
+}
+
+impl TermVector {
+    /// Build a normalized term frequency vector from text
+    pub fn from_text(text: &str) -> Self {
+        let mut weights: HashMap<String, f64> = HashMap::new();
+        for token in tokenize(text) {
+            *weights.entry(token.term).or_insert(0.0) += 1.0;
+        }
+
+        let norm = weights.values().map(|w| w * w).sum::<f64>().sqrt();
+        if norm > 0.0 {
+            for w in weights.values_mut() {
+                *w /= norm;
+            }
+        }
+
+        Self { weights }
+    }
+
+    /// Build a vector for a whole document from its chunks (in the code scope)
+    pub fn from_chunks(chunks: &[Chunk]) -> Self {
+        // Chunks overlap slightly, which barely changes the direction of the vector
+        let text: String = chunks
+            .iter()
+            .map(|c| c.search_text())
+            .collect::<Vec<_>>()
+            .join(" ");
+        Self::from_text(&text)
+    }
+
+    /// Cosine similarity in [0, 1] (both vectors are already normalized)
+    pub fn cosine(&self, other: &TermVector) -> f64 {
+        let (small, large) = if self.weights.len() <= other.weights.len() {
+            (&self.weights, &other.weights)
+        } else {
+            (&other.weights, &self.weights)
+        };
+
+        small
+            .iter()
+            .filter_map(|(term, w)| large.get(term).map(|o| w * o))
+            .sum::<f64>()
+            .clamp(0.0, 1.0)
+    }
+
+    pub fn contains(&self, term: &str) -> bool {
+        self.weights.contains_key(term)
+    }
+}
+
+/// Reference document prepared for comparison against candidate files
+pub struct ReferenceDocument {
+    pub vector: TermVector,
+    chunks: Vec<(Chunk, TermVector)>,
+}
+
+impl ReferenceDocument {
+    pub fn new(chunks: Vec<Chunk>) -> Self {
+        let vector = TermVector::from_chunks(&chunks);
+        let chunks = chunks
+            .into_iter()
+            .map(|c| {
+                let v = TermVector::from_text(c.search_text());
+                (c, v)
+            })
+            .collect();
+
+        Self { vector, chunks }
+    }
+
+    /// Pair every candidate chunk with its most similar reference chunk.
+    ///
+    /// The returned indices mark the candidate tokens that also occur in the
+    /// paired reference chunk, so the usual highlighting shows the overlap.
+    pub fn pair_chunks(&self, candidate: Vec<Chunk>) -> Vec<ScoredChunk> {
+        candidate
+            .into_par_iter()
+            .with_min_len(50)
+            .filter_map(|chunk| {
+                let vector = TermVector::from_text(chunk.search_text());
+                let (reference, ref_vector, score) = self
+                    .chunks
+                    .iter()
+                    .map(|(c, v)| (c, v, vector.cosine(v)))
+                    .max_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))?;
+
+                // Tokens never overlap, so indices come out sorted and unique
+                let indices: Vec<usize> = tokenize(chunk.search_text())
+                    .into_iter()
+                    .filter(|t| ref_vector.contains(&t.term))
+                    .flat_map(|t| t.start..t.start + t.len)
+                    .collect();
+
+                Some(ScoredChunk {
+                    score,
+                    indices: (!indices.is_empty()).then_some(indices),
+                    chunk,
+                    reference_chunk: Some(reference.clone()),
+                    term_scores: None,
+                })
+            })
+            .collect()
+    }
+}
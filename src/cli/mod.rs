@@ -1,47 +1,151 @@
-use std::path::PathBuf;
+use std::{io::IsTerminal, path::PathBuf};
 
-use crate::config::{Config, SimilarityAlgorithm};
+use crate::config::{
+    ChunkingStrategy, CodeScope, Config, OutputFormat, ScoreAggregation, SimilarityAlgorithm,
+    file::{ConfigLayer, ConfigSources, ValueSource, config_file_paths, load_layered},
+};
+use crate::errors::ConfigFileError;
+use crate::presentation::MatchDetail;
 
 #[derive(clap::Parser, Debug)]
 #[command(name = "doc-simfinder")]
 pub struct CliArgs {
-    /// Path to search
-    #[arg(long, default_value = ".")]
-    pub search_path: PathBuf,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to search [default: .]
+    #[arg(long)]
+    pub search_path: Option<PathBuf>,
 
     /// Query string to search for
     #[arg(long)]
     pub query: Option<String>,
 
+    /// Find files similar to this document instead of matching a query
+    #[arg(long, value_name = "FILE", conflicts_with = "query")]
+    pub like: Option<PathBuf>,
+
+    /// Report clusters of near-duplicate files instead of searching
+    #[arg(long, action)]
+    pub dups: bool,
+
     /// TUI mode (interactive terminal UI with state machine)
     #[arg(long, action)]
     pub tui: bool,
 
+    /// Named profile of doc-simfinder.toml to apply ([profile.NAME])
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+
+    // Flags below are optional so config files can provide them,
+    // defaults come from `Config::default()`
     /// Window size in characters
-    #[arg(long, default_value_t = 500)]
-    pub window_size: usize,
+    #[arg(long)]
+    pub window_size: Option<usize>,
 
     /// Maximum window size
-    #[arg(long, default_value_t = 5000)]
-    pub max_window_size: usize,
+    #[arg(long)]
+    pub max_window_size: Option<usize>,
 
     /// File extensions to include (comma separated)
     #[arg(long, value_delimiter = ',')]
     pub file_exts: Vec<String>,
 
     /// Similarity algorithm
-    #[arg(long, value_enum, default_value_t = Algorithm::Fuzzy)]
-    pub algorithm: Algorithm,
+    #[arg(long, value_enum)]
+    pub algorithm: Option<Algorithm>,
 
     /// Threshold
-    #[arg(long, short, default_value_t = 0.75_f64)]
-    pub threshold: f64,
+    #[arg(long, short)]
+    pub threshold: Option<f64>,
+
+    /// Number of top chunks shown per file
+    #[arg(long, short = 'n')]
+    pub top_n: Option<usize>,
+
+    /// How chunk scores are combined into a file score
+    #[arg(long, value_enum)]
+    pub aggregation: Option<Aggregation>,
+
+    /// How files are split into chunks
+    #[arg(long, value_enum)]
+    pub chunking: Option<Chunking>,
+
+    /// Only search comments, string literals or identifiers of source files
+    #[arg(long, value_enum)]
+    pub scope: Option<Scope>,
+
+    /// Number of worker threads (0 uses all cores)
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Output format of the results [default: text]
+    #[arg(long, value_enum)]
+    pub format: Option<Format>,
+
+    /// Also write the results to FILE (.txt, .json, .ndjson, .csv, .md or .html)
+    #[arg(long, short, value_name = "FILE", conflicts_with = "dups")]
+    pub output: Option<PathBuf>,
+
+    /// Keep extracted text and chunks in FILE and only re-read changed files
+    #[arg(long, value_name = "FILE")]
+    pub index: Option<PathBuf>,
+
+    /// Only print the paths of matching files
+    #[arg(long, action, conflicts_with_all = ["count", "dups", "format"])]
+    pub files_only: bool,
+
+    /// Only print the number of matching files
+    #[arg(long, action, conflicts_with_all = ["dups", "format"])]
+    pub count: bool,
+
+    /// Disable colors (also disabled by NO_COLOR or when stdout is not a terminal)
+    #[arg(long, action)]
+    pub no_color: bool,
+}
+
+impl CliArgs {
+    /// Whether text output is colored
+    pub fn use_color(&self) -> bool {
+        let no_color_env = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        !self.no_color && !no_color_env && std::io::stdout().is_terminal()
+    }
+
+    /// How matching files are printed in text mode
+    pub fn match_detail(&self) -> MatchDetail {
+        if self.count {
+            MatchDetail::Count
+        } else if self.files_only {
+            MatchDetail::FilesOnly
+        } else {
+            MatchDetail::Chunks
+        }
+    }
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum Command {
+    /// Inspect the configuration files (doc-simfinder.toml)
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommand,
+    },
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Print the effective configuration and where each value came from
+    Show,
 }
 
 #[derive(Clone, Debug, clap::ValueEnum)]
 pub enum Algorithm {
     Fuzzy,
     Lcs,
+    Bm25,
+    Boolean,
+    Regex,
+    EditDistance,
 }
 
 impl From<Algorithm> for SimilarityAlgorithm {
@@ -49,25 +153,129 @@ impl From<Algorithm> for SimilarityAlgorithm {
         match a {
             Algorithm::Fuzzy => SimilarityAlgorithm::Fuzzy,
             Algorithm::Lcs => SimilarityAlgorithm::LCS,
+            Algorithm::Bm25 => SimilarityAlgorithm::Bm25,
+            Algorithm::Boolean => SimilarityAlgorithm::Boolean,
+            Algorithm::Regex => SimilarityAlgorithm::Regex,
+            Algorithm::EditDistance => SimilarityAlgorithm::EditDistance,
+        }
+    }
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum Aggregation {
+    Max,
+    MeanTopK,
+    DiminishingSum,
+    Coverage,
+}
+
+impl From<Aggregation> for ScoreAggregation {
+    fn from(a: Aggregation) -> ScoreAggregation {
+        match a {
+            Aggregation::Max => ScoreAggregation::Max,
+            Aggregation::MeanTopK => ScoreAggregation::MeanTopK,
+            Aggregation::DiminishingSum => ScoreAggregation::DiminishingSum,
+            Aggregation::Coverage => ScoreAggregation::Coverage,
+        }
+    }
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum Chunking {
+    Fixed,
+    Sentence,
+    Structure,
+}
+
+impl From<Chunking> for ChunkingStrategy {
+    fn from(c: Chunking) -> ChunkingStrategy {
+        match c {
+            Chunking::Fixed => ChunkingStrategy::FixedWindow,
+            Chunking::Sentence => ChunkingStrategy::Sentence,
+            Chunking::Structure => ChunkingStrategy::Structure,
         }
     }
 }
 
-pub fn build_config_from_args(args: &CliArgs) -> Config {
-    let file_exts = if args.file_exts.is_empty() {
-        vec![".txt".to_string(), ".md".to_string()]
-    } else {
-        args.file_exts.clone()
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum Scope {
+    All,
+    Comments,
+    Strings,
+    Identifiers,
+}
+
+impl From<Scope> for CodeScope {
+    fn from(s: Scope) -> CodeScope {
+        match s {
+            Scope::All => CodeScope::All,
+            Scope::Comments => CodeScope::Comments,
+            Scope::Strings => CodeScope::Strings,
+            Scope::Identifiers => CodeScope::Identifiers,
+        }
+    }
+}
+
+#[derive(Clone, Debug, clap::ValueEnum)]
+pub enum Format {
+    Text,
+    Json,
+    Ndjson,
+    Vimgrep,
+}
+
+impl From<Format> for OutputFormat {
+    fn from(f: Format) -> OutputFormat {
+        match f {
+            Format::Text => OutputFormat::Text,
+            Format::Json => OutputFormat::Json,
+            Format::Ndjson => OutputFormat::Ndjson,
+            Format::Vimgrep => OutputFormat::Vimgrep,
+        }
+    }
+}
+
+pub fn build_config_from_args(args: &CliArgs) -> Result<Config, ConfigFileError> {
+    resolve_config(args, &config_file_paths()).map(|(config, _)| config)
+}
+
+/// Layer the config `files` (lowest priority first), the selected profile and
+/// the command line flags onto the defaults, see `config::file`
+pub fn resolve_config(
+    args: &CliArgs,
+    files: &[PathBuf],
+) -> Result<(Config, ConfigSources), ConfigFileError> {
+    // The CLI searches the current directory unless told otherwise
+    let base = Config {
+        search_path: PathBuf::from("."),
+        ..Default::default()
     };
+    let (mut config, mut sources) = load_layered(base, files, args.profile.as_deref())?;
+    cli_layer(args).apply(&mut config, &mut sources, &ValueSource::CommandLine);
 
-    Config {
+    config.query = args.query.clone().unwrap_or_default();
+    config.reference_file = args.like.clone();
+    config.duplicates = args.dups;
+    config.output_file = args.output.clone();
+
+    Ok((config, sources))
+}
+
+fn cli_layer(args: &CliArgs) -> ConfigLayer {
+    ConfigLayer {
         search_path: args.search_path.clone(),
-        query: args.query.clone().unwrap_or("".to_string()),
+        threads: args.threads,
+        file_exts: (!args.file_exts.is_empty()).then(|| args.file_exts.clone()),
+        algorithm: args.algorithm.clone().map(Into::into),
+        threshold: args.threshold,
+        top_n: args.top_n,
         window_size: args.window_size,
         max_window_size: args.max_window_size,
-        file_exts,
-        algorithm: args.algorithm.clone().into(),
-        threshold: args.threshold,
+        aggregation: args.aggregation.clone().map(Into::into),
+        chunking: args.chunking.clone().map(Into::into),
+        scope: args.scope.clone().map(Into::into),
+        format: args.format.clone().map(Into::into),
+        index: args.index.clone(),
         ..Default::default()
     }
 }
@@ -0,0 +1,230 @@
+// Configuration files (doc-simfinder.toml) and named profiles
+//
+// Values are layered: `Config::default()`, the user file in the XDG config
+// dir, the project file in the current directory, the selected profile of
+// both files and finally the command line flags. Only values that are set
+// override the layer below.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    ChunkingStrategy, CodeScope, Config, OutputFormat, ScoreAggregation, SimilarityAlgorithm,
+};
+use crate::errors::ConfigFileError;
+
+pub const CONFIG_FILE_NAME: &str = "doc-simfinder.toml";
+
+/// Optional config values, from a file, a profile or the command line.
+/// Keys match the command line flags (`threads`, `scope`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigLayer {
+    pub search_path: Option<PathBuf>,
+    pub max_search_depth: Option<usize>,
+    pub threads: Option<usize>,
+    pub file_exts: Option<Vec<String>>,
+    pub algorithm: Option<SimilarityAlgorithm>,
+    pub threshold: Option<f64>,
+    pub window_size: Option<usize>,
+    pub max_window_size: Option<usize>,
+    pub top_n: Option<usize>,
+    pub aggregation: Option<ScoreAggregation>,
+    pub chunking: Option<ChunkingStrategy>,
+    pub scope: Option<CodeScope>,
+    pub format: Option<OutputFormat>,
+    pub index: Option<PathBuf>,
+}
+
+impl ConfigLayer {
+    /// Set the values present in this layer, recording where they came from
+    pub fn apply(&self, config: &mut Config, sources: &mut ConfigSources, source: &ValueSource) {
+        let mut set = Setter { sources, source };
+        set.value(&mut config.search_path, &self.search_path, "search_path");
+        set.value(
+            &mut config.max_search_depth,
+            &self.max_search_depth,
+            "max_search_depth",
+        );
+        set.value(&mut config.num_threads, &self.threads, "threads");
+        set.value(&mut config.file_exts, &self.file_exts, "file_exts");
+        set.value(&mut config.algorithm, &self.algorithm, "algorithm");
+        set.value(&mut config.threshold, &self.threshold, "threshold");
+        set.value(&mut config.window_size, &self.window_size, "window_size");
+        set.value(
+            &mut config.max_window_size,
+            &self.max_window_size,
+            "max_window_size",
+        );
+        set.value(&mut config.top_n, &self.top_n, "top_n");
+        set.value(&mut config.aggregation, &self.aggregation, "aggregation");
+        set.value(&mut config.chunking, &self.chunking, "chunking");
+        set.value(&mut config.code_scope, &self.scope, "scope");
+        set.value(&mut config.output_format, &self.format, "format");
+        set.value(
+            &mut config.index_file,
+            &self.index.clone().map(Some),
+            "index",
+        );
+    }
+}
+
+struct Setter<'a> {
+    sources: &'a mut ConfigSources,
+    source: &'a ValueSource,
+}
+
+impl Setter<'_> {
+    fn value<T: Clone>(&mut self, target: &mut T, value: &Option<T>, key: &'static str) {
+        if let Some(value) = value {
+            *target = value.clone();
+            self.sources.0.insert(key, self.source.clone());
+        }
+    }
+}
+
+/// Every layered value of a config, in file order (see `config show`)
+impl From<&Config> for ConfigLayer {
+    fn from(config: &Config) -> Self {
+        Self {
+            search_path: Some(config.search_path.clone()),
+            max_search_depth: Some(config.max_search_depth),
+            threads: Some(config.num_threads),
+            file_exts: Some(config.file_exts.clone()),
+            algorithm: Some(config.algorithm.clone()),
+            threshold: Some(config.threshold),
+            window_size: Some(config.window_size),
+            max_window_size: Some(config.max_window_size),
+            top_n: Some(config.top_n),
+            aggregation: Some(config.aggregation),
+            chunking: Some(config.chunking),
+            scope: Some(config.code_scope),
+            format: Some(config.output_format),
+            // Left out when no index is used
+            index: config.index_file.clone(),
+        }
+    }
+}
+
+/// A doc-simfinder.toml file: base values and `[profile.NAME]` tables
+#[derive(Debug, Clone, Default)]
+pub struct ConfigFile {
+    pub base: ConfigLayer,
+    pub profile: BTreeMap<String, ConfigLayer>,
+}
+
+impl ConfigFile {
+    pub fn load(path: &Path) -> Result<Self, ConfigFileError> {
+        let text = fs::read_to_string(path).map_err(|source| ConfigFileError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        Self::parse(&text).map_err(|source| ConfigFileError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    // Split off the profiles by hand, unknown keys aren't caught through `#[serde(flatten)]`
+    fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(text)?;
+        let profile = match table.remove("profile") {
+            Some(profiles) => profiles.try_into()?,
+            None => BTreeMap::new(),
+        };
+        Ok(Self {
+            base: table.try_into()?,
+            profile,
+        })
+    }
+}
+
+/// Where a config value was set
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueSource {
+    Default,
+    File(PathBuf),
+    Profile { name: String, file: PathBuf },
+    CommandLine,
+}
+
+impl fmt::Display for ValueSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueSource::Default => write!(f, "default"),
+            ValueSource::File(file) => write!(f, "{}", file.display()),
+            ValueSource::Profile { name, file } => {
+                write!(f, "profile '{}' in {}", name, file.display())
+            }
+            ValueSource::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+/// Source of each layered value, keyed like `ConfigLayer`
+#[derive(Debug, Clone, Default)]
+pub struct ConfigSources(HashMap<&'static str, ValueSource>);
+
+impl ConfigSources {
+    pub fn get(&self, key: &str) -> &ValueSource {
+        self.0.get(key).unwrap_or(&ValueSource::Default)
+    }
+}
+
+/// Config files in increasing priority: user (XDG config dir), then project
+pub fn config_file_paths() -> Vec<PathBuf> {
+    let user_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")));
+
+    let mut paths: Vec<PathBuf> = user_dir
+        .into_iter()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .collect();
+    paths.push(PathBuf::from(CONFIG_FILE_NAME));
+    paths
+}
+
+/// Layer the existing `files` (lowest priority first) and `profile` onto `base`.
+/// Missing files are skipped, an unknown profile is an error
+pub fn load_layered(
+    base: Config,
+    files: &[PathBuf],
+    profile: Option<&str>,
+) -> Result<(Config, ConfigSources), ConfigFileError> {
+    let mut config = base;
+    let mut sources = ConfigSources::default();
+
+    let mut loaded = Vec::new();
+    for path in files.iter().filter(|p| p.is_file()) {
+        let file = ConfigFile::load(path)?;
+        file.base
+            .apply(&mut config, &mut sources, &ValueSource::File(path.clone()));
+        loaded.push((path, file));
+    }
+
+    if let Some(name) = profile {
+        let mut found = false;
+        for (path, file) in &loaded {
+            if let Some(layer) = file.profile.get(name) {
+                let source = ValueSource::Profile {
+                    name: name.to_string(),
+                    file: path.to_path_buf(),
+                };
+                layer.apply(&mut config, &mut sources, &source);
+                found = true;
+            }
+        }
+        if !found {
+            return Err(ConfigFileError::UnknownProfile(name.to_string()));
+        }
+    }
+
+    Ok((config, sources))
+}
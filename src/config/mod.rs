@@ -1,4 +1,8 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::errors::ConfigError;
+
+pub mod file;
 
 // The config struct is what's being created by either the interactive
 // or the one-shot command.
@@ -13,11 +17,19 @@ pub struct Config {
     pub num_threads: usize,
     // File extensions
     pub file_exts: Vec<String>,
-    // Output file
+    // Report file written after a CLI search, also the TUI export target
     pub output_file: Option<PathBuf>,
+    // How CLI results are printed
+    pub output_format: OutputFormat,
+    // Persistent index of extracted text and chunks, reused across runs
+    pub index_file: Option<PathBuf>,
 
     // Query string
     pub query: String,
+    // Reference document for query by example (replaces the query string)
+    pub reference_file: Option<PathBuf>,
+    // Cluster near-duplicate files instead of searching (no query needed)
+    pub duplicates: bool,
 
     // Analysis algorithm
     pub algorithm: SimilarityAlgorithm,
@@ -30,6 +42,12 @@ pub struct Config {
 
     // Number of top N chunks per file
     pub top_n: usize,
+    // How chunk scores are combined into the file score
+    pub aggregation: ScoreAggregation,
+    // How file content is split into chunks
+    pub chunking: ChunkingStrategy,
+    // Part of source files a query is matched against
+    pub code_scope: CodeScope,
 }
 
 // Allowed file extensions
@@ -42,49 +60,99 @@ pub static ALLOWED_UTF8_FILE_EXTS: &[&str] = &[
 pub static ALLOWED_BINARY_FILE_EXTS: &[&str] = &[".pdf"];
 
 impl Config {
+    /// First problem of the config, see `validation_errors`
     pub fn validate(&self) -> Result<(), ConfigError> {
-        // Basic validation used by CLI and programmatic callers
-        if self.query.is_empty() {
-            return Err(ConfigError);
+        match self.validation_errors().into_iter().next() {
+            Some(e) => Err(e),
+            None => Ok(()),
         }
+    }
+
+    /// Every problem of the config, used by CLI and programmatic callers
+    /// and re-checked by the TUI on each edit
+    pub fn validation_errors(&self) -> Vec<ConfigError> {
+        let mut errors = Vec::new();
 
         if !self.search_path.exists() {
-            return Err(ConfigError);
+            errors.push(ConfigError::SearchPathNotFound(self.search_path.clone()));
         }
 
-        if self.window_size == 0 || self.max_window_size == 0 {
-            return Err(ConfigError);
+        if self.max_search_depth == 0 {
+            errors.push(ConfigError::SearchDepthZero);
         }
 
         if self.file_exts.is_empty() {
-            return Err(ConfigError);
+            errors.push(ConfigError::NoFileExtensions);
         }
 
-        if self.threshold < 0.0 || self.threshold > 1.0 {
-            return Err(ConfigError);
+        if let Some(ext) = self.file_exts.iter().find(|ext| {
+            !ALLOWED_BINARY_FILE_EXTS.contains(&ext.as_str())
+                && !ALLOWED_UTF8_FILE_EXTS.contains(&ext.as_str())
+        }) {
+            errors.push(ConfigError::UnsupportedExtension(ext.clone()));
         }
 
-        if self.top_n == 0 {
-            return Err(ConfigError);
+        // The query only matters when searching for it
+        match &self.reference_file {
+            Some(reference) if !reference.is_file() => {
+                errors.push(ConfigError::ReferenceNotFound(reference.clone()));
+            }
+            Some(_) => {}
+            None if self.duplicates => {}
+            None if self.query.is_empty() => errors.push(ConfigError::MissingQuery),
+            None => match self.algorithm {
+                SimilarityAlgorithm::Boolean => {
+                    if let Err(e) = crate::analysis::query::parse_query(&self.query) {
+                        errors.push(e.into());
+                    }
+                }
+                SimilarityAlgorithm::Regex => {
+                    if let Err(e) = regex::Regex::new(&self.query) {
+                        errors.push(ConfigError::InvalidRegex(regex_error_message(&e)));
+                    }
+                }
+                _ => {}
+            },
         }
 
-        if self.max_search_depth == 0 {
-            return Err(ConfigError);
+        if !(0.0..=1.0).contains(&self.threshold) {
+            errors.push(ConfigError::ThresholdOutOfRange(self.threshold));
         }
 
-        if self.file_exts.iter().any(|ext| {
-            !ALLOWED_BINARY_FILE_EXTS.contains(&ext.as_str())
-                && !ALLOWED_UTF8_FILE_EXTS.contains(&ext.as_str())
-        }) {
-            return Err(ConfigError);
+        if self.window_size == 0 {
+            errors.push(ConfigError::WindowSizeZero);
+        } else if self.window_size > self.max_window_size {
+            errors.push(ConfigError::WindowTooLarge {
+                window: self.window_size,
+                max: self.max_window_size,
+            });
+        }
+
+        if self.top_n == 0 {
+            errors.push(ConfigError::TopNZero);
         }
 
-        // More validation needed here...
+        if let Some(path) = &self.output_file
+            && ReportFormat::from_path(path).is_none()
+        {
+            errors.push(ConfigError::UnsupportedOutputFile(path.clone()));
+        }
 
-        Ok(())
+        errors
     }
 }
 
+// Regex errors span several lines (pattern, caret, message), keep the message
+fn regex_error_message(e: &regex::Error) -> String {
+    let message = e.to_string();
+    message
+        .lines()
+        .last()
+        .unwrap_or_default()
+        .trim_start_matches("error: ")
+        .to_string()
+}
+
 // Default values for config
 impl Default for Config {
     fn default() -> Self {
@@ -94,22 +162,154 @@ impl Default for Config {
             num_threads: 0, // 0 means all threads are used
             file_exts: vec![".txt".to_string(), ".md".to_string()], // TODO! extend these
             output_file: None,
+            output_format: OutputFormat::Text,
+            index_file: None,
             query: Default::default(),
+            reference_file: None,
+            duplicates: false,
             algorithm: SimilarityAlgorithm::Fuzzy,
             threshold: 0.75_f64,
             window_size: 500,
             max_window_size: 5000,
             top_n: 5,
+            aggregation: ScoreAggregation::Max,
+            chunking: ChunkingStrategy::FixedWindow,
+            code_scope: CodeScope::All,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+// Config files use the same names as the command line flags
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum SimilarityAlgorithm {
     Fuzzy,
+    #[serde(rename = "lcs")]
     LCS,
+    // Ranked lexical search, document frequencies over all walked files
+    Bm25,
+    // AND/OR/NOT query language with "phrases" and -excluded terms
+    Boolean,
+    // Query is a regular expression
+    Regex,
+    // Word-level Damerau-Levenshtein matching, tolerant to typos
+    EditDistance,
 }
 
-// Put in errors.rs
-#[derive(Debug, Clone)]
-pub struct ConfigError;
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    // Human readable lines
+    Text,
+    // One JSON document with all results, see docs/JSON_OUTPUT.md
+    Json,
+    // One JSON object per file and line
+    Ndjson,
+    // `path:line:col:text` per top chunk, for editor quickfix lists
+    Vimgrep,
+}
+
+// Report file format (--output, TUI export), chosen by the file extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    // Same lines as the CLI text output, without colors
+    Text,
+    Json,
+    Ndjson,
+    // One row per top chunk
+    Csv,
+    Markdown,
+    // Single file with inline CSS, see presentation::html
+    Html,
+}
+
+impl ReportFormat {
+    /// Format for a file extension, `None` if unsupported
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "txt" => Some(ReportFormat::Text),
+            "json" => Some(ReportFormat::Json),
+            "ndjson" | "jsonl" => Some(ReportFormat::Ndjson),
+            "csv" => Some(ReportFormat::Csv),
+            "md" | "markdown" => Some(ReportFormat::Markdown),
+            "html" | "htm" => Some(ReportFormat::Html),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChunkingStrategy {
+    // Fixed windows of window_size chars with 10% overlap
+    #[serde(rename = "fixed")]
+    FixedWindow,
+    // Whole sentences up to window_size chars, breaking at paragraphs
+    Sentence,
+    // Markdown split by headings (with breadcrumbs), source code by items
+    // (with symbol names), other files by sentences
+    Structure,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodeScope {
+    // Whole file
+    All,
+    // Only comments of source files
+    Comments,
+    // Only string literals of source files
+    Strings,
+    // Only identifiers (no keywords) of source files
+    Identifiers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScoreAggregation {
+    // Best chunk score
+    Max,
+    // Mean of the top N chunk scores
+    MeanTopK,
+    // 1 - prod(1 - score), each extra match adds less
+    DiminishingSum,
+    // Best score weighted by the share of chunks above threshold
+    Coverage,
+}
+
+impl ScoreAggregation {
+    /// Cycle to the next strategy (used by the TUI options panel)
+    pub fn next(self) -> Self {
+        match self {
+            ScoreAggregation::Max => ScoreAggregation::MeanTopK,
+            ScoreAggregation::MeanTopK => ScoreAggregation::DiminishingSum,
+            ScoreAggregation::DiminishingSum => ScoreAggregation::Coverage,
+            ScoreAggregation::Coverage => ScoreAggregation::Max,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ScoreAggregation::Max => "Max",
+            ScoreAggregation::MeanTopK => "Mean of top N",
+            ScoreAggregation::DiminishingSum => "Diminishing sum",
+            ScoreAggregation::Coverage => "Coverage-weighted",
+        }
+    }
+}
+
+/// Fields of `Config` that can be invalid, see `ConfigError::field`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigField {
+    SearchPath,
+    MaxSearchDepth,
+    FileExts,
+    Query,
+    ReferenceFile,
+    Threshold,
+    WindowSize,
+    MaxWindowSize,
+    TopN,
+    OutputFile,
+}
@@ -1,3 +1,7 @@
+use std::path::PathBuf;
+
+use crate::config::ConfigField;
+
 #[derive(Debug, thiserror::Error)]
 pub enum WalkError {
     #[error("Failed to build glob pattern: {0}")]
@@ -16,10 +20,138 @@ pub enum ChunkError {
     BinaryFile(String),
     #[error("Pdf processing error: {0}")]
     PdfProcessing(String),
+    #[error("PDF too large ({0}MB > 10MB)")]
+    PdfTooLarge(u64),
+    #[error("Parser panicked: {0}")]
+    Panicked(String),
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Search path does not exist: {}", .0.display())]
+    SearchPathNotFound(PathBuf),
+    #[error("Max search depth must be at least 1")]
+    SearchDepthZero,
+    #[error("No file extensions given")]
+    NoFileExtensions,
+    #[error("Unsupported file extension: {0}")]
+    UnsupportedExtension(String),
+    #[error("Query is empty")]
+    MissingQuery,
+    #[error("Invalid query: {0}")]
+    InvalidQuery(#[from] QueryParseError),
+    #[error("Invalid regex: {0}")]
+    InvalidRegex(String),
+    #[error("Reference file not found: {}", .0.display())]
+    ReferenceNotFound(PathBuf),
+    #[error("Threshold {0} is out of range [0, 1]")]
+    ThresholdOutOfRange(f64),
+    #[error("Window size must be at least 1")]
+    WindowSizeZero,
+    #[error("Window size {window} is larger than the max window size {max}")]
+    WindowTooLarge { window: usize, max: usize },
+    #[error("Top N must be at least 1")]
+    TopNZero,
+    #[error("Unsupported output file: {} (use .txt, .json, .ndjson, .csv, .md or .html)", .0.display())]
+    UnsupportedOutputFile(PathBuf),
+}
+
+impl ConfigError {
+    /// Config field the error is about, used to show it next to that field
+    pub fn field(&self) -> ConfigField {
+        match self {
+            ConfigError::SearchPathNotFound(_) => ConfigField::SearchPath,
+            ConfigError::SearchDepthZero => ConfigField::MaxSearchDepth,
+            ConfigError::NoFileExtensions | ConfigError::UnsupportedExtension(_) => {
+                ConfigField::FileExts
+            }
+            ConfigError::MissingQuery
+            | ConfigError::InvalidQuery(_)
+            | ConfigError::InvalidRegex(_) => ConfigField::Query,
+            ConfigError::ReferenceNotFound(_) => ConfigField::ReferenceFile,
+            ConfigError::ThresholdOutOfRange(_) => ConfigField::Threshold,
+            ConfigError::WindowSizeZero => ConfigField::WindowSize,
+            ConfigError::WindowTooLarge { .. } => ConfigField::MaxWindowSize,
+            ConfigError::TopNZero => ConfigField::TopN,
+            ConfigError::UnsupportedOutputFile(_) => ConfigField::OutputFile,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigFileError {
+    #[error("Failed to read {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Invalid config file {}: {source}", path.display())]
+    Parse {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Unknown profile '{0}'")]
+    UnknownProfile(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ReportError {
+    #[error("Unsupported report format: {} (use .txt, .json, .ndjson, .csv, .md or .html)", .0.display())]
+    UnsupportedFormat(PathBuf),
+    #[error("Failed to write {}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum IndexError {
+    #[error("Failed to read index {}: {source}", path.display())]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to write index {}: {source}", path.display())]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Failed to encode index: {0}")]
+    Encode(#[from] bincode::Error),
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum QueryParseError {
+    #[error("Query is empty")]
+    Empty,
+    #[error("Unterminated quote in query")]
+    UnterminatedQuote,
+    #[error("Unbalanced parenthesis in query")]
+    UnbalancedParenthesis,
+    #[error("Missing operand for {0}")]
+    MissingOperand(String),
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum ScoreError {
     #[error("Error processing chunks: {0}")]
     ChunkError(#[from] ChunkError),
+    #[error("Analysis was cancelled")]
+    Cancelled,
+}
+
+impl ScoreError {
+    /// Short reason a file was skipped, for summaries
+    pub fn reason(&self) -> &'static str {
+        match self {
+            ScoreError::ChunkError(ChunkError::Io(_)) => "read error",
+            ScoreError::ChunkError(ChunkError::InvalidUtf8(_)) => "invalid UTF-8",
+            ScoreError::ChunkError(ChunkError::BinaryFile(_)) => "binary",
+            ScoreError::ChunkError(ChunkError::PdfProcessing(_)) => "PDF error",
+            ScoreError::ChunkError(ChunkError::PdfTooLarge(_)) => "PDF too large",
+            ScoreError::ChunkError(ChunkError::Panicked(_)) => "parser panic",
+            ScoreError::Cancelled => "cancelled",
+        }
+    }
 }
@@ -0,0 +1,214 @@
+// Persistent index of extracted text and chunks (Config::index_file, --index)
+//
+// Entries are keyed by canonical path and stamped with the modification time
+// and size of the file. Unchanged files are served from the index, changed
+// ones are read again and replaced. Chunks are kept for the chunking
+// parameters they were made with; other parameters re-chunk the stored text,
+// so PDFs are only extracted once.
+//
+// Files that fail to read are not stored and are retried on the next run.
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Mutex, MutexGuard, PoisonError,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::SystemTime,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    analysis::{Chunk, SlidingWindow, bm25::FileTermStats, chunk_content, read_content},
+    config::{ChunkingStrategy, CodeScope},
+    errors::{ChunkError, IndexError},
+};
+
+/// Bump on changes to the stored types (including `Chunk`), older indexes are rebuilt
+pub const INDEX_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct FileStamp {
+    modified: Option<SystemTime>,
+    size: u64,
+}
+
+/// Parameters that change the chunks of a text
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct ChunkKey {
+    window_size: usize,
+    overlap: usize,
+    strategy: ChunkingStrategy,
+    code_scope: CodeScope,
+}
+
+impl From<&SlidingWindow> for ChunkKey {
+    fn from(window: &SlidingWindow) -> Self {
+        Self {
+            window_size: window.window_size,
+            overlap: window.overlap,
+            strategy: window.strategy,
+            code_scope: window.code_scope,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    stamp: FileStamp,
+    text: String,
+    // Chunks of the last analysis of the file
+    key: ChunkKey,
+    chunks: Vec<Chunk>,
+    terms: FileTermStats,
+}
+
+/// Chunks of a file with their term statistics
+#[derive(Debug, Clone)]
+pub struct IndexedChunks {
+    pub chunks: Vec<Chunk>,
+    pub terms: FileTermStats,
+}
+
+/// What an analysis did with the index
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IndexStats {
+    // Files in the saved index
+    pub files: usize,
+    // Unchanged files whose text came from the index
+    pub reused: usize,
+    // New or changed files read from disk
+    pub indexed: usize,
+    // Entries of deleted files dropped on save
+    pub removed: usize,
+}
+
+pub struct Index {
+    path: PathBuf,
+    files: Mutex<HashMap<PathBuf, IndexEntry>>,
+    reused: AtomicUsize,
+    indexed: AtomicUsize,
+}
+
+impl Index {
+    /// Load the index at `path`. A missing, outdated or unreadable index
+    /// starts empty and is rebuilt; only I/O errors fail
+    pub fn open(path: &Path) -> Result<Self, IndexError> {
+        let files = match fs::read(path) {
+            Ok(bytes) => decode(&bytes).unwrap_or_default(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(source) => {
+                return Err(IndexError::Read {
+                    path: path.to_path_buf(),
+                    source,
+                });
+            }
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            files: Mutex::new(files),
+            reused: AtomicUsize::new(0),
+            indexed: AtomicUsize::new(0),
+        })
+    }
+
+    /// Chunks of `file`, from the index if the file did not change since
+    pub fn chunks(&self, file: &Path, window: &SlidingWindow) -> Result<IndexedChunks, ChunkError> {
+        let metadata = fs::metadata(file)?;
+        let stamp = FileStamp {
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+        };
+        let key = ChunkKey::from(window);
+        let path = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+
+        let cached = match self.lock().get(&path) {
+            Some(entry) if entry.stamp == stamp && entry.key == key => {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                return Ok(IndexedChunks {
+                    chunks: entry.chunks.clone(),
+                    terms: entry.terms.clone(),
+                });
+            }
+            // Same text, other chunking parameters
+            Some(entry) if entry.stamp == stamp => Some(entry.text.clone()),
+            _ => None,
+        };
+
+        // Read outside the lock, extracting PDFs takes a while
+        let text = match cached {
+            Some(text) => {
+                self.reused.fetch_add(1, Ordering::Relaxed);
+                text
+            }
+            None => {
+                let text = read_content(file)?;
+                self.indexed.fetch_add(1, Ordering::Relaxed);
+                text
+            }
+        };
+        let chunks = chunk_content(file, &text, window);
+        let terms = FileTermStats::from_chunks(&chunks);
+
+        self.lock().insert(
+            path,
+            IndexEntry {
+                stamp,
+                text,
+                key,
+                chunks: chunks.clone(),
+                terms: terms.clone(),
+            },
+        );
+        Ok(IndexedChunks { chunks, terms })
+    }
+
+    /// Drop deleted files and write the index, replacing the file atomically
+    pub fn save(&self) -> Result<IndexStats, IndexError> {
+        let mut files = self.lock();
+        let before = files.len();
+        files.retain(|path, _| path.exists());
+
+        let bytes = bincode::serialize(&(INDEX_VERSION, &*files))?;
+        let write_error = |source| IndexError::Write {
+            path: self.path.clone(),
+            source,
+        };
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir).map_err(write_error)?;
+        }
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = self.path.with_file_name(format!(".{}.tmp", name));
+        fs::write(&tmp, bytes).map_err(write_error)?;
+        fs::rename(&tmp, &self.path).map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            write_error(e)
+        })?;
+
+        Ok(IndexStats {
+            files: files.len(),
+            reused: self.reused.load(Ordering::Relaxed),
+            indexed: self.indexed.load(Ordering::Relaxed),
+            removed: before - files.len(),
+        })
+    }
+
+    // A panic while holding the lock leaves the map consistent, entries are inserted whole
+    fn lock(&self) -> MutexGuard<'_, HashMap<PathBuf, IndexEntry>> {
+        self.files.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// The version comes first, so older layouts are recognized before decoding entries
+fn decode(bytes: &[u8]) -> Option<HashMap<PathBuf, IndexEntry>> {
+    let version: u32 = bincode::deserialize(bytes).ok()?;
+    if version != INDEX_VERSION {
+        return None;
+    }
+    let (_, files): (u32, HashMap<PathBuf, IndexEntry>) = bincode::deserialize(bytes).ok()?;
+    Some(files)
+}
@@ -3,6 +3,7 @@ pub mod cli;
 pub mod config;
 pub mod errors;
 pub mod file_walker;
+pub mod index;
 pub mod presentation;
 
 // TUI feature modules
@@ -1,16 +1,41 @@
 use clap::Parser;
-use std::process::exit;
+use std::{process::exit, time::Instant};
 
 use doc_simfinder::{
     analysis::analyse_files,
-    cli::{CliArgs, build_config_from_args},
+    cli::{CliArgs, Command, ConfigCommand, build_config_from_args, resolve_config},
+    config::{OutputFormat, file::config_file_paths},
     file_walker::walk_from_root,
+    presentation::{
+        MatchDetail, best_matches,
+        json::{present_json, present_ndjson_line},
+        present_effective_config, present_matches, present_skipped_files, present_vimgrep,
+        report::{Report, write_report},
+    },
 };
 
 #[tokio::main]
 async fn main() {
     let args = CliArgs::parse();
 
+    if let Some(Command::Config {
+        action: ConfigCommand::Show,
+    }) = &args.command
+    {
+        match resolve_config(&args, &config_file_paths()) {
+            Ok((config, sources)) => print!("{}", present_effective_config(&config, &sources)),
+            Err(e) => {
+                eprintln!("{}", e);
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    if !args.use_color() {
+        colored::control::set_override(false);
+    }
+
     // Check if TUI mode is requested
     if args.tui {
         if let Err(e) = run_tui_mode(&args).await {
@@ -20,32 +45,103 @@ async fn main() {
         return;
     }
 
-    // CLI mode requires a query
-    if args.query.is_none() {
-        eprintln!("Error: --query is required in CLI mode. Use --tui for interactive mode.");
+    // CLI mode requires a query or a reference document (except for duplicate detection)
+    if args.query.is_none() && args.like.is_none() && !args.dups {
+        eprintln!(
+            "Error: --query or --like is required in CLI mode. Use --tui for interactive mode."
+        );
         exit(1);
     }
 
-    let config = build_config_from_args(&args);
+    let config = match build_config_from_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            exit(1);
+        }
+    };
+
+    if let Err(e) = config.validate() {
+        eprintln!("Invalid configuration: {}", e);
+        exit(1);
+    }
 
-    if let Err(_) = config.validate() {
-        eprintln!("Invalid configuration. Check search path, query and window sizes.");
+    if config.duplicates && config.output_format != OutputFormat::Text {
+        eprintln!("Error: --dups only supports text output");
         exit(1);
     }
 
+    let detail = args.match_detail();
+    // --files-only and --count are plain text, even with a format from a config file
+    let format = match detail {
+        MatchDetail::Chunks => config.output_format,
+        _ => OutputFormat::Text,
+    };
     match walk_from_root(&config) {
         Ok(walk) => {
             if walk.files.is_empty() {
-                println!("No files found under {}", config.search_path.display());
+                // Still an empty document for JSON, NDJSON just has no lines
+                if format == OutputFormat::Json {
+                    println!("{}", present_json(&[], &[]));
+                }
+                eprintln!("No files found under {}", config.search_path.display());
+                return;
+            }
+
+            if config.duplicates {
+                run_duplicates_mode(&walk.files, &config);
                 return;
             }
 
             // Use analyse_files to process all files in parallel
+            let started = Instant::now();
             match analyse_files(&walk.files, &config) {
-                Ok(file_scores) => {
-                    // Print results in CLI mode
-                    for score in file_scores.iter() {
-                        println!("File: {} (score: {:.2})", score.path.display(), score.score);
+                Ok(report) => {
+                    match format {
+                        OutputFormat::Text => {
+                            // Print results in CLI mode
+                            print!("{}", present_matches(&report.scores, &config, detail));
+
+                            // Summary on stderr, results on stdout stay clean
+                            eprint!("{}", present_skipped_files(&report.failures));
+                        }
+                        // Skipped files are part of the document
+                        OutputFormat::Json => {
+                            let matches = best_matches(&report.scores, config.threshold);
+                            println!("{}", present_json(&matches, &report.skipped()));
+                        }
+                        OutputFormat::Vimgrep => {
+                            print!("{}", present_vimgrep(&report.scores, &config));
+                            eprint!("{}", present_skipped_files(&report.failures));
+                        }
+                        OutputFormat::Ndjson => {
+                            for score in best_matches(&report.scores, config.threshold) {
+                                println!("{}", present_ndjson_line(score));
+                            }
+                            eprint!("{}", present_skipped_files(&report.failures));
+                        }
+                    }
+
+                    // The results are complete either way, the next run just reads more files
+                    if let Some(Err(e)) = &report.index {
+                        eprintln!("Index not saved: {}", e);
+                    }
+
+                    if let Some(path) = &config.output_file {
+                        let skipped = report.skipped();
+                        let report = Report {
+                            scores: &report.scores,
+                            skipped: &skipped,
+                            config: &config,
+                            elapsed: Some(started.elapsed()),
+                        };
+                        match write_report(path, &report) {
+                            Ok(()) => eprintln!("Results written to {}", path.display()),
+                            Err(e) => {
+                                eprintln!("{}", e);
+                                exit(1);
+                            }
+                        }
                     }
                 }
                 Err(err) => {
@@ -61,6 +157,31 @@ async fn main() {
     }
 }
 
+/// Print clusters of near-duplicate files
+fn run_duplicates_mode(files: &[std::path::PathBuf], config: &doc_simfinder::config::Config) {
+    use doc_simfinder::{analysis::dups::find_duplicates, presentation::present_duplicate_cluster};
+
+    match find_duplicates(files, config) {
+        Ok(report) => {
+            if report.clusters.is_empty() {
+                println!(
+                    "No near-duplicates found among {} files (threshold {:.2})",
+                    files.len(),
+                    config.threshold
+                );
+            }
+            for (i, cluster) in report.clusters.iter().enumerate() {
+                println!("{}", present_duplicate_cluster(cluster, i + 1));
+            }
+            eprint!("{}", present_skipped_files(&report.failures));
+        }
+        Err(err) => {
+            eprintln!("Failed to find duplicates: {}", err);
+            exit(1);
+        }
+    }
+}
+
 /// Run the advanced TUI mode with state machine
 async fn run_tui_mode(args: &CliArgs) -> Result<(), Box<dyn std::error::Error>> {
     use doc_simfinder::{
@@ -69,12 +190,12 @@ async fn run_tui_mode(args: &CliArgs) -> Result<(), Box<dyn std::error::Error>>
     };
 
     // Build initial config
-    let config = build_config_from_args(args);
+    let config = build_config_from_args(args)?;
 
-    // Create initial state
+    // Create initial state, flagging invalid arguments right away
     let initial_state = AppState::Configuring {
+        validation_errors: config.validation_errors(),
         config,
-        validation_errors: vec![],
         walk_result: None,
         autocomplete_available: false,
         autocomplete_suggestion: None,
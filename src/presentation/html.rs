@@ -0,0 +1,234 @@
+// Self-contained HTML report (--output report.html, TUI export)
+//
+// One file with inline CSS and no scripts, so it can be mailed or attached
+// as is. Files link to their absolute `file://` path.
+
+use std::{
+    collections::HashSet,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+use super::{format_position, format_term_scores, report::Report};
+use crate::{
+    analysis::{FileScore, ScoredChunk},
+    config::{Config, file::ConfigLayer},
+};
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2rem auto; max-width: 70rem; padding: 0 1rem; color: #222; }
+h1 { margin-bottom: 0.2rem; }
+.summary { color: #666; margin-top: 0; }
+table { border-collapse: collapse; }
+td, th { border-bottom: 1px solid #ddd; padding: 0.2rem 0.8rem 0.2rem 0; text-align: left; vertical-align: top; }
+code, pre { font-family: ui-monospace, monospace; font-size: 0.9rem; }
+.file { border: 1px solid #ddd; border-radius: 6px; margin: 1rem 0; padding: 0.5rem 1rem; }
+.file h3 { margin: 0.3rem 0; font-size: 1rem; }
+.score { background: #e8f0fe; border-radius: 4px; color: #1a4fa0; font-weight: bold; padding: 0 0.4rem; }
+.meta { color: #666; font-size: 0.85rem; }
+.location { color: #0b7285; }
+pre { background: #f6f8fa; border-radius: 4px; overflow-x: auto; padding: 0.6rem; white-space: pre-wrap; }
+mark { background: #ffe066; font-weight: bold; }
+.skipped td:first-child { color: #c92a2a; }
+";
+
+/// The whole report as one HTML document, `matches` being its `best_matches`
+pub fn present_html(matches: &[&FileScore], report: &Report) -> String {
+    let Report {
+        scores,
+        skipped,
+        config,
+        elapsed,
+    } = *report;
+
+    let mut out = String::new();
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>doc-simfinder results</title>\n<style>{}</style>\n</head>\n<body>\n",
+        STYLE
+    );
+
+    out.push_str("<h1>doc-simfinder results</h1>\n");
+    let mut summary = format!(
+        "{} matching file(s) of {}, {} skipped",
+        matches.len(),
+        scores.len(),
+        skipped.len()
+    );
+    if let Some(elapsed) = elapsed {
+        let _ = write!(summary, ", {:.2}s", elapsed.as_secs_f64());
+    }
+    let _ = writeln!(out, "<p class=\"summary\">{}</p>", summary);
+
+    render_config(&mut out, config);
+
+    out.push_str("<h2>Results</h2>\n");
+    if matches.is_empty() {
+        out.push_str("<p>No matching files.</p>\n");
+    }
+    for (i, score) in matches.iter().enumerate() {
+        render_file(&mut out, i + 1, score);
+    }
+
+    if !skipped.is_empty() {
+        out.push_str("<h2>Skipped files</h2>\n<table class=\"skipped\">\n");
+        out.push_str("<tr><th>Reason</th><th>File</th><th>Error</th></tr>\n");
+        for s in skipped {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td><code>{}</code></td><td>{}</td></tr>",
+                escape(s.reason),
+                escape(&s.path.display().to_string()),
+                escape(&s.message)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+// Query (or reference document) followed by the layered config values
+fn render_config(out: &mut String, config: &Config) {
+    out.push_str("<h2>Configuration</h2>\n<table>\n");
+    let mut row = |key: &str, value: &str| {
+        let _ = writeln!(
+            out,
+            "<tr><th>{}</th><td><code>{}</code></td></tr>",
+            escape(key),
+            escape(value)
+        );
+    };
+
+    match &config.reference_file {
+        Some(reference) => row("like", &reference.display().to_string()),
+        None => row("query", &config.query),
+    }
+    // Same keys and values as `config show`
+    let table = toml::Table::try_from(ConfigLayer::from(config)).unwrap_or_default();
+    for (key, value) in &table {
+        row(key, &value.to_string());
+    }
+    out.push_str("</table>\n");
+}
+
+fn render_file(out: &mut String, rank: usize, score: &FileScore) {
+    let _ = write!(
+        out,
+        "<section class=\"file\">\n<h3>{}. <a href=\"{}\">{}</a> <span class=\"score\">{:.2}</span></h3>\n",
+        rank,
+        escape(&file_url(&score.path)),
+        escape(&score.path.display().to_string()),
+        score.score
+    );
+    if let Some(duration) = score.analysis_duration {
+        let _ = writeln!(
+            out,
+            "<div class=\"meta\">analysed in {:.1}ms</div>",
+            duration.as_secs_f64() * 1000.0
+        );
+    }
+
+    for chunk in &score.top_chunks {
+        let mut meta = format!(
+            "<code>{}</code> &middot; score {:.2}",
+            escape(&format_position(&score.path, chunk)),
+            chunk.score
+        );
+        if let Some(location) = chunk.chunk.location() {
+            let _ = write!(
+                meta,
+                " &middot; <span class=\"location\">{}</span>",
+                escape(location)
+            );
+        }
+        if let Some(terms) = &chunk.term_scores {
+            let _ = write!(
+                meta,
+                " &middot; terms: {}",
+                escape(&format_term_scores(terms))
+            );
+        }
+        if let Some(reference) = &chunk.reference_chunk {
+            let _ = write!(
+                meta,
+                " &middot; similar to reference at {}:{}",
+                reference.line, reference.column
+            );
+        }
+        let _ = writeln!(
+            out,
+            "<div class=\"meta\">{}</div>\n<pre>{}</pre>",
+            meta,
+            highlight(chunk)
+        );
+    }
+    out.push_str("</section>\n");
+}
+
+// Escaped chunk text with runs of matched chars in <mark>
+fn highlight(chunk: &ScoredChunk) -> String {
+    let indices: HashSet<usize> = chunk.indices.iter().flatten().copied().collect();
+    let mut out = String::new();
+    let mut marked = false;
+    for (i, c) in chunk.chunk.text.chars().enumerate() {
+        let matched = indices.contains(&i);
+        if matched != marked {
+            out.push_str(if matched { "<mark>" } else { "</mark>" });
+            marked = matched;
+        }
+        push_escaped(&mut out, c);
+    }
+    if marked {
+        out.push_str("</mark>");
+    }
+    out
+}
+
+// Absolute file:// URL, the report is usually opened from another directory
+fn file_url(path: &Path) -> String {
+    let absolute = path
+        .canonicalize()
+        .or_else(|_| std::env::current_dir().map(|dir| dir.join(path)))
+        .unwrap_or_else(|_| PathBuf::from(path));
+    // `canonicalize` returns verbatim paths on Windows (\\?\C:\...)
+    let absolute = absolute.to_string_lossy().replace('\\', "/");
+    let absolute = absolute.trim_start_matches("//?/");
+
+    let mut url = String::from("file://");
+    if !absolute.starts_with('/') {
+        url.push('/');
+    }
+    for byte in absolute.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' | b':' => {
+                url.push(byte as char)
+            }
+            _ => {
+                let _ = write!(url, "%{:02X}", byte);
+            }
+        }
+    }
+    url
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        _ => out.push(c),
+    }
+}
@@ -0,0 +1,136 @@
+// JSON and NDJSON output of search results
+//
+// The schema is documented in docs/JSON_OUTPUT.md. It is defined here rather
+// than by deriving `Serialize` on the analysis types, so internal changes
+// don't break scripts. Bump `SCHEMA_VERSION` on incompatible changes.
+
+use serde::Serialize;
+
+use crate::analysis::{Chunk, FileScore, ScoredChunk, SkippedFile};
+
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    schema_version: u32,
+    results: Vec<JsonFileScore<'a>>,
+    skipped: Vec<JsonSkippedFile>,
+}
+
+#[derive(Serialize)]
+struct JsonFileScore<'a> {
+    path: String,
+    score: f64,
+    analysis_duration_ms: Option<f64>,
+    top_chunks: Vec<JsonChunk<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonChunk<'a> {
+    score: f64,
+    #[serde(flatten)]
+    location: JsonLocation<'a>,
+    // Char indices into `text` of the matched characters
+    matched_indices: Option<&'a [usize]>,
+    term_scores: Option<Vec<JsonTermScore<'a>>>,
+    // Most similar chunk of the reference document (--like only)
+    reference: Option<JsonLocation<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonLocation<'a> {
+    text: &'a str,
+    start_char: usize,
+    end_char: usize,
+    start_byte: usize,
+    end_byte: usize,
+    line: usize,
+    column: usize,
+    heading_path: Option<&'a str>,
+    symbol: Option<&'a str>,
+}
+
+#[derive(Serialize)]
+struct JsonTermScore<'a> {
+    term: &'a str,
+    score: f64,
+    negated: bool,
+}
+
+#[derive(Serialize)]
+struct JsonSkippedFile {
+    path: String,
+    reason: &'static str,
+    message: String,
+}
+
+impl<'a> From<&'a FileScore> for JsonFileScore<'a> {
+    fn from(score: &'a FileScore) -> Self {
+        Self {
+            path: score.path.to_string_lossy().into_owned(),
+            score: score.score,
+            analysis_duration_ms: score.analysis_duration.map(|d| d.as_secs_f64() * 1000.0),
+            top_chunks: score.top_chunks.iter().map(JsonChunk::from).collect(),
+        }
+    }
+}
+
+impl<'a> From<&'a ScoredChunk> for JsonChunk<'a> {
+    fn from(chunk: &'a ScoredChunk) -> Self {
+        Self {
+            score: chunk.score,
+            location: JsonLocation::from(&chunk.chunk),
+            matched_indices: chunk.indices.as_deref(),
+            term_scores: chunk.term_scores.as_ref().map(|terms| {
+                terms
+                    .iter()
+                    .map(|t| JsonTermScore {
+                        term: &t.term,
+                        score: t.score,
+                        negated: t.negated,
+                    })
+                    .collect()
+            }),
+            reference: chunk.reference_chunk.as_ref().map(JsonLocation::from),
+        }
+    }
+}
+
+impl<'a> From<&'a Chunk> for JsonLocation<'a> {
+    fn from(chunk: &'a Chunk) -> Self {
+        Self {
+            text: &chunk.text,
+            start_char: chunk.start_char,
+            end_char: chunk.end_char,
+            start_byte: chunk.start_byte,
+            end_byte: chunk.end_byte,
+            line: chunk.line,
+            column: chunk.column,
+            heading_path: chunk.heading_path.as_deref(),
+            symbol: chunk.symbol.as_deref(),
+        }
+    }
+}
+
+/// Results (see `best_matches`) and skipped files as one pretty-printed JSON document
+pub fn present_json(scores: &[&FileScore], skipped: &[SkippedFile]) -> String {
+    let report = JsonReport {
+        schema_version: SCHEMA_VERSION,
+        results: scores.iter().map(|s| JsonFileScore::from(*s)).collect(),
+        skipped: skipped
+            .iter()
+            .map(|s| JsonSkippedFile {
+                path: s.path.to_string_lossy().into_owned(),
+                reason: s.reason,
+                message: s.message.clone(),
+            })
+            .collect(),
+    };
+    // Only string keys and plain values, serializing can't fail
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// One file result as a single line of JSON (no trailing newline)
+pub fn present_ndjson_line(score: &FileScore) -> String {
+    serde_json::to_string(&JsonFileScore::from(score)).unwrap_or_default()
+}
@@ -1,7 +1,20 @@
-use crate::{analysis::FileScore, config::Config};
+use crate::{
+    analysis::{FileScore, ScoredChunk, dups::DuplicateCluster, query::TermScore, read_content},
+    config::{
+        Config,
+        file::{ConfigLayer, ConfigSources},
+    },
+    errors::ScoreError,
+};
 use colored::*;
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+pub mod html;
+pub mod json;
+pub mod report;
 
 // Helper to format a snippet with highlighted indices.
 // If indices are provided, matched character positions are underlined and bold yellow.
@@ -13,22 +26,24 @@ fn format_snippet_with_highlights(
 ) -> String {
     match indices {
         Some(idx_vec) if !idx_vec.is_empty() => {
+            // Indices are char positions, so slice by chars
+            let chars: Vec<char> = full_text.chars().collect();
             let min_idx = *idx_vec.iter().min().unwrap();
             let max_idx = *idx_vec.iter().max().unwrap();
 
             // Pad around the match: 60 chars before, or to start if not enough
-            let snippet_start = if min_idx > 60 { min_idx - 60 } else { 0 };
+            let snippet_start = min_idx.saturating_sub(60).min(chars.len());
 
             // Extend end to capture match range + padding, up to max_snippet_len
-            let snippet_end = (snippet_start + max_snippet_len).min(full_text.len());
+            let snippet_end = (snippet_start + max_snippet_len).min(chars.len());
             // Ensure we capture at least to the max_idx
-            let snippet_end = snippet_end.max(max_idx + 1).min(full_text.len());
+            let snippet_end = snippet_end.max(max_idx + 1).min(chars.len());
 
-            let snippet_sub = &full_text[snippet_start..snippet_end.min(full_text.len())];
+            let snippet_sub: String = chars[snippet_start..snippet_end].iter().collect();
 
             // First, wrap the plain text to terminal width BEFORE coloring
             let width = term_size::dimensions().map(|(w, _)| w).unwrap_or(80);
-            let wrapped_plain = textwrap::fill(snippet_sub, width);
+            let wrapped_plain = textwrap::fill(&snippet_sub, width);
 
             // Map global indices to positions in the wrapped snippet
             let highlight_indices: HashSet<_> = idx_vec
@@ -44,7 +59,7 @@ fn format_snippet_with_highlights(
 
             // Build colored snippet from the wrapped text
             let mut colored = String::new();
-            for (i, ch) in wrapped_plain.char_indices() {
+            for (i, ch) in wrapped_plain.chars().enumerate() {
                 if highlight_indices.contains(&i) {
                     write!(colored, "{}", ch.to_string().underline().bold().yellow()).ok();
                 } else {
@@ -58,8 +73,8 @@ fn format_snippet_with_highlights(
             // No indices or empty: just return first line, truncated
             let first_line = full_text.lines().next().unwrap_or("");
             let trimmed = first_line.trim();
-            if trimmed.len() > 300 {
-                format!("{}...", &trimmed[..300])
+            if trimmed.chars().count() > 300 {
+                format!("{}...", trimmed.chars().take(300).collect::<String>())
             } else {
                 trimmed.to_string()
             }
@@ -67,6 +82,43 @@ fn format_snippet_with_highlights(
     }
 }
 
+// Per-term breakdown of a boolean query score, e.g. `error 1.00, handling 0.64, -panic`
+pub fn format_term_scores(terms: &[TermScore]) -> String {
+    terms
+        .iter()
+        .map(|t| t.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// "path:line:col" of the first match in a chunk
+pub fn format_position(path: &Path, chunk: &ScoredChunk) -> String {
+    let (line, column) = chunk.position();
+    format!("{}:{}:{}", path.display(), line, column)
+}
+
+/// One line per file and per top chunk position, without colors
+pub fn present_score_lines(scores: &[&FileScore]) -> String {
+    let mut out = String::new();
+    for score in scores {
+        let _ = writeln!(
+            out,
+            "File: {} (score: {:.2})",
+            score.path.display(),
+            score.score
+        );
+        for chunk in &score.top_chunks {
+            let _ = writeln!(
+                out,
+                "  {} (score: {:.2})",
+                format_position(&score.path, chunk),
+                chunk.score
+            );
+        }
+    }
+    out
+}
+
 // Presentation helpers for CLI output with colored indices and scores.
 // This returns an ANSI-colored string; callers that need plain text
 // can strip ANSI codes.
@@ -78,7 +130,7 @@ pub fn present_file_score(score: &FileScore, _config: &Config) -> String {
 
     let analysis_duration = match score.analysis_duration {
         Some(duration) => format!("Analysis duration: {:?}", duration).red().italic(),
-        None => format!("--").red(),
+        None => "--".to_string().red(),
     };
 
     let _ = writeln!(out, "{}", analysis_duration);
@@ -100,22 +152,208 @@ pub fn present_file_score(score: &FileScore, _config: &Config) -> String {
     for (i, c) in score.top_chunks.iter().enumerate() {
         // Add separator before each chunk (except the first one)
         if i > 0 {
-            let _ = writeln!(out, "");
+            let _ = writeln!(out);
             let _ = writeln!(out, "{}", "─".repeat(80).dimmed());
-            let _ = writeln!(out, "");
+            let _ = writeln!(out);
         }
 
         let idx = format!("{}.", i + 1).bold().yellow();
         let sc = format!("{:.4}", c.score).magenta();
-        let range = format!("[{}..{}]", c.chunk.start_byte, c.chunk.end_byte).dimmed();
-        let context_header = format!("Context:").underline();
+        let position = format_position(&score.path, c).dimmed();
+        let context_header = "Context:".to_string().underline();
 
         let formatted_snippet = format_snippet_with_highlights(&c.chunk.text, &c.indices, 300);
 
-        let _ = writeln!(out, "  {} score: {} {}", idx, sc, range);
+        let _ = writeln!(out, "  {} score: {} {}", idx, sc, position);
+        if let Some(location) = c.chunk.location() {
+            let _ = writeln!(out, "     {}", location.cyan());
+        }
+        if let Some(reference) = &c.reference_chunk {
+            let reference_position = format!("at {}:{}", reference.line, reference.column).dimmed();
+            let _ = writeln!(out, "     similar to reference {}", reference_position);
+        }
+        if let Some(terms) = &c.term_scores {
+            let _ = writeln!(out, "     terms: {}", format_term_scores(terms).dimmed());
+        }
         let _ = writeln!(out, "{}", context_header);
         let _ = writeln!(out, "     {}", formatted_snippet);
     }
 
     out
 }
+
+/// How much of each matching file the CLI prints
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchDetail {
+    // Top chunks with highlighted context (`present_file_score`)
+    Chunks,
+    // One path per line (--files-only)
+    FilesOnly,
+    // Number of matching files (--count)
+    Count,
+}
+
+/// Files above threshold, best first: what every output format lists
+pub fn best_matches(scores: &[FileScore], threshold: f64) -> Vec<&FileScore> {
+    let mut matches: Vec<&FileScore> = scores.iter().filter(|s| s.is_match(threshold)).collect();
+    matches.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal));
+    matches
+}
+
+// Present the files above threshold, best first, as text.
+pub fn present_matches(scores: &[FileScore], config: &Config, detail: MatchDetail) -> String {
+    let matches = best_matches(scores, config.threshold);
+    match detail {
+        MatchDetail::Chunks => matches
+            .iter()
+            .map(|score| present_file_score(score, config))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        MatchDetail::FilesOnly => matches
+            .iter()
+            .map(|score| format!("{}\n", score.path.display()))
+            .collect(),
+        MatchDetail::Count => format!("{}\n", matches.len()),
+    }
+}
+
+// Present one `path:line:col:text` line per top chunk (like `rg --vimgrep`),
+// at the first matched character. Columns count bytes, as Vim and rg do, and
+// the text is the whole line of the file. Overlapping chunks sharing their
+// first match are listed once.
+pub fn present_vimgrep(scores: &[FileScore], config: &Config) -> String {
+    let mut out = String::new();
+    for score in best_matches(scores, config.threshold) {
+        // Without the file (e.g. moved since) the line is cut from the chunk
+        let content = read_content(&score.path).ok();
+        let mut seen = HashSet::new();
+        for chunk in &score.top_chunks {
+            let (line, column) = chunk.byte_position();
+            if !seen.insert((line, column)) {
+                continue;
+            }
+            let text = content
+                .as_deref()
+                .and_then(|content| source_line(content, chunk))
+                .unwrap_or_else(|| match_line(chunk));
+            let _ = writeln!(out, "{}:{}:{}:{}", score.path.display(), line, column, text);
+        }
+    }
+    out
+}
+
+// Byte offset into the chunk text of the first match (0 without matches)
+fn first_match_byte(chunk: &ScoredChunk) -> usize {
+    let first = chunk.indices.as_ref().and_then(|i| i.iter().min().copied());
+    first
+        .and_then(|i| chunk.chunk.text.char_indices().nth(i))
+        .map_or(0, |(byte, _)| byte)
+}
+
+// Line of the file holding the first match, `None` if the file changed since
+fn source_line<'a>(content: &'a str, chunk: &ScoredChunk) -> Option<&'a str> {
+    let range = chunk.chunk.start_byte..chunk.chunk.end_byte;
+    if content.get(range)? != chunk.chunk.text {
+        return None;
+    }
+    let at = chunk.chunk.start_byte + first_match_byte(chunk);
+    let start = content[..at].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[at..].find('\n').map_or(content.len(), |i| at + i);
+    Some(content[start..end].trim_end_matches('\r'))
+}
+
+// Line of the chunk text holding the first match (the first line without matches)
+fn match_line(chunk: &ScoredChunk) -> &str {
+    let text = &chunk.chunk.text;
+    let at = first_match_byte(chunk);
+    let start = text[..at].rfind('\n').map_or(0, |i| i + 1);
+    let end = text[at..].find('\n').map_or(text.len(), |i| at + i);
+    text[start..end].trim_end_matches('\r')
+}
+
+// Present a near-duplicate cluster: member files followed by pairwise similarities.
+pub fn present_duplicate_cluster(cluster: &DuplicateCluster, number: usize) -> String {
+    let mut out = String::new();
+
+    let header = format!("Cluster {} ({} files)", number, cluster.files.len())
+        .bold()
+        .cyan();
+    let _ = writeln!(out, "{}", header);
+
+    for file in &cluster.files {
+        let _ = writeln!(out, "  - {}", file.display());
+    }
+
+    let _ = writeln!(out, "{}", "Pairwise similarity:".bold());
+    for pair in &cluster.pairs {
+        let similarity = format!("{:.4}", pair.similarity).magenta();
+        let _ = writeln!(
+            out,
+            "  {} {} <-> {}",
+            similarity,
+            pair.first.display(),
+            pair.second.display()
+        );
+    }
+
+    out
+}
+
+// Present files that could not be analysed as a table: reason, file, error message.
+pub fn present_skipped_files(failures: &[(PathBuf, ScoreError)]) -> String {
+    let mut out = String::new();
+    if failures.is_empty() {
+        return out;
+    }
+
+    let rows: Vec<(&str, String, String)> = failures
+        .iter()
+        .map(|(path, e)| (e.reason(), path.display().to_string(), e.to_string()))
+        .collect();
+    let reason_width = rows.iter().map(|r| r.0.len()).max().unwrap_or(0).max(6);
+    let file_width = rows
+        .iter()
+        .map(|r| r.1.chars().count())
+        .max()
+        .unwrap_or(0)
+        .max(4);
+
+    let header = format!("Skipped {} file(s)", failures.len())
+        .bold()
+        .yellow();
+    let _ = writeln!(out, "{}", header);
+    let columns = format!(
+        "  {:<reason_width$}  {:<file_width$}  {}",
+        "REASON", "FILE", "ERROR"
+    );
+    let _ = writeln!(out, "{}", columns.bold());
+    for (reason, file, message) in rows {
+        let _ = writeln!(
+            out,
+            "  {:<reason_width$}  {:<file_width$}  {}",
+            reason, file, message
+        );
+    }
+
+    out
+}
+
+// Present the effective config as TOML, each value followed by where it was set.
+pub fn present_effective_config(config: &Config, sources: &ConfigSources) -> String {
+    let mut out = String::new();
+    // Every field but `index` is set, only non UTF-8 paths can't be serialized
+    let table = toml::Table::try_from(ConfigLayer::from(config)).unwrap_or_default();
+
+    let lines: Vec<(String, &str)> = table
+        .iter()
+        .map(|(key, value)| (format!("{} = {}", key, value), key.as_str()))
+        .collect();
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+
+    for (line, key) in lines {
+        let source = format!("# {}", sources.get(key));
+        let _ = writeln!(out, "{:<width$}  {}", line, source.dimmed());
+    }
+
+    out
+}
@@ -0,0 +1,185 @@
+// Result reports written to a file (--output, export from the TUI results view)
+//
+// The format follows the file extension. Reports are written to a temporary
+// file next to the target and renamed, so readers never see a partial report.
+
+use std::{
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use super::{
+    best_matches, format_position,
+    html::present_html,
+    json::{present_json, present_ndjson_line},
+    present_score_lines,
+};
+use crate::{
+    analysis::{FileScore, SkippedFile},
+    config::{Config, ReportFormat},
+    errors::ReportError,
+};
+
+/// Export target of the TUI results view when no `--output` is given
+pub const DEFAULT_REPORT_FILE: &str = "doc-simfinder-results.md";
+
+/// Results of one analysis, as written to a report
+pub struct Report<'a> {
+    pub scores: &'a [FileScore],
+    pub skipped: &'a [SkippedFile],
+    // Effective config and total duration, shown in HTML reports
+    pub config: &'a Config,
+    pub elapsed: Option<Duration>,
+}
+
+pub fn render_report(format: ReportFormat, report: &Report) -> String {
+    // Every format lists the same files as the CLI output
+    let matches = best_matches(report.scores, report.config.threshold);
+    let skipped = report.skipped;
+    match format {
+        ReportFormat::Text => render_text(&matches, skipped),
+        ReportFormat::Json => present_json(&matches, skipped) + "\n",
+        ReportFormat::Ndjson => matches
+            .iter()
+            .map(|score| present_ndjson_line(score) + "\n")
+            .collect(),
+        ReportFormat::Csv => render_csv(&matches),
+        ReportFormat::Markdown => render_markdown(&matches, skipped),
+        ReportFormat::Html => present_html(&matches, report),
+    }
+}
+
+/// Write the report for `path`'s extension, replacing the file atomically
+pub fn write_report(path: &Path, report: &Report) -> Result<(), ReportError> {
+    let format = ReportFormat::from_path(path)
+        .ok_or_else(|| ReportError::UnsupportedFormat(path.to_path_buf()))?;
+    let rendered = render_report(format, report);
+
+    let io_error = |source| ReportError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let tmp = temp_path(path);
+    fs::write(&tmp, rendered).map_err(io_error)?;
+    fs::rename(&tmp, path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        io_error(e)
+    })
+}
+
+// Same directory, so the rename stays on one filesystem
+fn temp_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{}.tmp", name))
+}
+
+fn render_text(scores: &[&FileScore], skipped: &[SkippedFile]) -> String {
+    let mut out = present_score_lines(scores);
+    if !skipped.is_empty() {
+        let _ = writeln!(out, "\nSkipped {} file(s)", skipped.len());
+        for s in skipped {
+            let _ = writeln!(out, "  {}  {}  {}", s.reason, s.path.display(), s.message);
+        }
+    }
+    out
+}
+
+fn render_csv(scores: &[&FileScore]) -> String {
+    let mut out = String::from(
+        "path,file_score,rank,chunk_score,line,column,start_byte,end_byte,heading_path,symbol,text\n",
+    );
+    for score in scores {
+        let path = score.path.display().to_string();
+        // Files without chunks (e.g. query by example) still get a row
+        if score.top_chunks.is_empty() {
+            let _ = writeln!(out, "{},{:.4},,,,,,,,,", csv_field(&path), score.score);
+        }
+        for (rank, chunk) in score.top_chunks.iter().enumerate() {
+            let (line, column) = chunk.position();
+            let _ = writeln!(
+                out,
+                "{},{:.4},{},{:.4},{},{},{},{},{},{},{}",
+                csv_field(&path),
+                score.score,
+                rank + 1,
+                chunk.score,
+                line,
+                column,
+                chunk.chunk.start_byte,
+                chunk.chunk.end_byte,
+                csv_field(chunk.chunk.heading_path.as_deref().unwrap_or_default()),
+                csv_field(chunk.chunk.symbol.as_deref().unwrap_or_default()),
+                csv_field(&chunk.chunk.text),
+            );
+        }
+    }
+    out
+}
+
+// RFC 4180: quote fields with separators, quotes or line breaks
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn render_markdown(scores: &[&FileScore], skipped: &[SkippedFile]) -> String {
+    let mut out = String::from("# doc-simfinder results\n\n");
+    if scores.is_empty() {
+        out.push_str("No matching files.\n");
+    } else {
+        out.push_str("| # | File | Score |\n|---|------|-------|\n");
+        for (i, score) in scores.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "| {} | `{}` | {:.2} |",
+                i + 1,
+                score.path.display(),
+                score.score
+            );
+        }
+    }
+
+    for (i, score) in scores.iter().enumerate() {
+        let _ = writeln!(
+            out,
+            "\n## {}. `{}` ({:.2})",
+            i + 1,
+            score.path.display(),
+            score.score
+        );
+        for chunk in &score.top_chunks {
+            let _ = write!(
+                out,
+                "\n- `{}` (score {:.2})",
+                format_position(&score.path, chunk),
+                chunk.score
+            );
+            if let Some(location) = chunk.chunk.location() {
+                let _ = write!(out, " {}", location);
+            }
+            out.push_str("\n\n");
+            for line in chunk.chunk.text.trim().lines() {
+                let _ = writeln!(out, "  > {}", line);
+            }
+        }
+    }
+
+    if !skipped.is_empty() {
+        let _ = writeln!(out, "\n## Skipped files\n");
+        for s in skipped {
+            let _ = writeln!(
+                out,
+                "- `{}`: {} ({})",
+                s.path.display(),
+                s.reason,
+                s.message
+            );
+        }
+    }
+    out
+}
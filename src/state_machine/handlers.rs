@@ -3,7 +3,7 @@
 // See docs/copilot/state-machine.md for input handling patterns
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
-use super::{AppState, StateEvent};
+use super::{AnalysisStatus, AppState, StateEvent};
 
 /// Trait for handling input in a specific state
 pub trait InputHandler {
@@ -30,13 +30,9 @@ impl InputHandler for ConfiguringHandler {
     fn handle_key(&self, key: KeyEvent, _state: &AppState) -> Vec<StateEvent> {
         let mut events = Vec::new();
 
-        match (key.code, key.modifiers) {
-            // Start analysis
-            (KeyCode::Enter, KeyModifiers::NONE) => {
-                events.push(StateEvent::StartAnalysis);
-            }
-
-            _ => {}
+        // Start analysis
+        if let (KeyCode::Enter, KeyModifiers::NONE) = (key.code, key.modifiers) {
+            events.push(StateEvent::StartAnalysis);
         }
 
         events
@@ -47,9 +43,12 @@ impl InputHandler for ConfiguringHandler {
 pub struct AnalyzingHandler;
 
 impl InputHandler for AnalyzingHandler {
-    fn handle_key(&self, _key: KeyEvent, _state: &AppState) -> Vec<StateEvent> {
-        // No user input during analysis except Ctrl+Q (handled globally)
-        vec![]
+    fn handle_key(&self, key: KeyEvent, _state: &AppState) -> Vec<StateEvent> {
+        // Only Esc to stop early, besides Ctrl+Q (handled globally)
+        match key.code {
+            KeyCode::Esc => vec![StateEvent::CancelAnalysis],
+            _ => vec![],
+        }
     }
 }
 
@@ -63,6 +62,7 @@ impl InputHandler for ResultsHandler {
         if let AppState::ViewingResults {
             selected_index,
             results,
+            status,
             ..
         } = state
         {
@@ -79,10 +79,8 @@ impl InputHandler for ResultsHandler {
                 KeyCode::Home => {
                     events.push(StateEvent::SelectFile(0));
                 }
-                KeyCode::End => {
-                    if !results.is_empty() {
-                        events.push(StateEvent::SelectFile(results.len() - 1));
-                    }
+                KeyCode::End if !results.is_empty() => {
+                    events.push(StateEvent::SelectFile(results.len() - 1));
                 }
                 KeyCode::PageUp => {
                     let new_index = selected_index.saturating_sub(10);
@@ -104,6 +102,9 @@ impl InputHandler for ResultsHandler {
                 KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                     events.push(StateEvent::OpenFileLocation);
                 }
+                KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    events.push(StateEvent::ExportResults);
+                }
 
                 // Sort mode cycling
                 KeyCode::Char('s') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -111,7 +112,10 @@ impl InputHandler for ResultsHandler {
                     // Implementation will cycle: ByScore -> ByName -> ByPath -> ByScore
                 }
 
-                // Go back
+                // Stop a still running analysis first, then go back
+                KeyCode::Esc if matches!(status, AnalysisStatus::Running(_)) => {
+                    events.push(StateEvent::CancelAnalysis);
+                }
                 KeyCode::Esc => {
                     events.push(StateEvent::GoBack);
                 }
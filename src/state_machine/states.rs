@@ -4,8 +4,9 @@
 
 use std::path::PathBuf;
 
-use crate::analysis::FileScore;
+use crate::analysis::{FileScore, Progress, SkippedFile};
 use crate::config::Config;
+use crate::errors::ConfigError;
 use crate::file_walker::WalkResult;
 
 /// Main application state enum
@@ -14,7 +15,7 @@ pub enum AppState {
     /// Initial configuration state where user sets up search parameters
     Configuring {
         config: Config,
-        validation_errors: Vec<String>,
+        validation_errors: Vec<ConfigError>,
         walk_result: Option<WalkResult>,
         autocomplete_available: bool,
         autocomplete_suggestion: Option<String>,
@@ -25,8 +26,8 @@ pub enum AppState {
         config: Config,
         path: PathBuf,
         query: String,
-        files_processed: usize,
-        total_files: usize,
+        // Latest progress report of the background task
+        progress: Progress,
     },
 
     /// Viewing analysis results
@@ -37,6 +38,11 @@ pub enum AppState {
         sort_mode: SortMode,
         filter: Option<String>,
         total_duration: Option<std::time::Duration>,
+        status: AnalysisStatus,
+        // Files that could not be analysed, known once the analysis is done
+        skipped: Vec<SkippedFile>,
+        // Outcome of the last export (Ctrl+E): the written file or the error
+        last_export: Option<Result<PathBuf, String>>,
     },
 
     /// Viewing detailed information about a specific file
@@ -78,6 +84,16 @@ impl AppState {
     }
 }
 
+/// Whether the results list is complete or still growing
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnalysisStatus {
+    /// Analysis continues in the background, files are inserted as they finish
+    Running(Progress),
+    Complete,
+    /// Analysis was cancelled, results only cover the files scored until then
+    Cancelled,
+}
+
 /// Sort mode for results view
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortMode {
@@ -99,12 +115,18 @@ pub enum StateEvent {
     StartAnalysis,
 
     // Analysis events
-    AnalysisProgress {
-        files_done: usize,
-        total: usize,
-    },
+    AnalysisProgress(Progress),
+    // A file finished scoring (streamed before AnalysisComplete)
+    AnalysisResult(FileScore),
     AnalysisComplete {
         results: Vec<FileScore>,
+        skipped: Vec<SkippedFile>,
+        elapsed: std::time::Duration,
+    },
+    CancelAnalysis,
+    AnalysisCancelled {
+        results: Vec<FileScore>,
+        skipped: Vec<SkippedFile>,
         elapsed: std::time::Duration,
     },
     AnalysisError(String),
@@ -123,6 +145,10 @@ pub enum StateEvent {
     // Action events
     Reanalyze,
     OpenFileLocation,
+    // Write the results list to a report file (done by the app)
+    ExportResults,
+    // Report path or error message of the export
+    ExportFinished(Result<PathBuf, String>),
 
     // File system events (for real-time updates)
     FileChanged(PathBuf),
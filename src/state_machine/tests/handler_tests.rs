@@ -25,3 +25,73 @@ fn test_configuring_handler() {
 
     assert_eq!(events.len(), 1);
 }
+
+#[test]
+fn test_analyzing_handler_esc_cancels() {
+    use crate::state_machine::{StateEvent, handlers::AnalyzingHandler};
+
+    let handler = AnalyzingHandler;
+    let state = AppState::Analyzing {
+        config: Config::default(),
+        path: Default::default(),
+        query: String::new(),
+        progress: Default::default(),
+    };
+
+    let events = handler.handle_key(KeyEvent::from(KeyCode::Esc), &state);
+    assert!(matches!(events.as_slice(), [StateEvent::CancelAnalysis]));
+
+    let events = handler.handle_key(KeyEvent::from(KeyCode::Enter), &state);
+    assert!(events.is_empty());
+}
+
+#[test]
+fn test_results_handler_esc_stops_running_analysis() {
+    use crate::state_machine::{AnalysisStatus, SortMode, StateEvent, handlers::ResultsHandler};
+
+    let handler = ResultsHandler;
+    let mut state = AppState::ViewingResults {
+        config: Config::default(),
+        results: vec![],
+        selected_index: 0,
+        sort_mode: SortMode::ByScore,
+        filter: None,
+        total_duration: None,
+        status: AnalysisStatus::Running(Default::default()),
+        skipped: vec![],
+        last_export: None,
+    };
+
+    let events = handler.handle_key(KeyEvent::from(KeyCode::Esc), &state);
+    assert!(matches!(events.as_slice(), [StateEvent::CancelAnalysis]));
+
+    if let AppState::ViewingResults { status, .. } = &mut state {
+        *status = AnalysisStatus::Cancelled;
+    }
+    let events = handler.handle_key(KeyEvent::from(KeyCode::Esc), &state);
+    assert!(matches!(events.as_slice(), [StateEvent::GoBack]));
+}
+
+#[test]
+fn test_results_handler_ctrl_e_exports() {
+    use crate::state_machine::{AnalysisStatus, SortMode, StateEvent, handlers::ResultsHandler};
+    use crossterm::event::KeyModifiers;
+
+    let state = AppState::ViewingResults {
+        config: Config::default(),
+        results: vec![],
+        selected_index: 0,
+        sort_mode: SortMode::ByScore,
+        filter: None,
+        total_duration: None,
+        status: AnalysisStatus::Complete,
+        skipped: vec![],
+        last_export: None,
+    };
+
+    let events = ResultsHandler.handle_key(
+        KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL),
+        &state,
+    );
+    assert!(matches!(events.as_slice(), [StateEvent::ExportResults]));
+}
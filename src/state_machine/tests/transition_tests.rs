@@ -2,8 +2,10 @@
 //
 // Moved from inline tests in src/state_machine/transitions.rs
 
+use crate::analysis::{FileScore, Progress, SkippedFile};
 use crate::config::Config;
-use crate::state_machine::{AppState, StateEvent, transition, TransitionResult};
+use crate::errors::ConfigError;
+use crate::state_machine::{AnalysisStatus, AppState, StateEvent, TransitionResult, transition};
 
 #[test]
 fn test_configuring_to_analyzing() {
@@ -24,6 +26,50 @@ fn test_configuring_to_analyzing() {
     }
 }
 
+#[test]
+fn test_validate_config_lists_errors() {
+    let mut state = AppState::Configuring {
+        config: Config {
+            search_path: "testdata".into(),
+            threshold: 2.0,
+            ..Default::default()
+        },
+        validation_errors: vec![],
+        walk_result: None,
+        autocomplete_available: false,
+        autocomplete_suggestion: None,
+    };
+
+    let result = transition(&mut state, StateEvent::ValidateConfig);
+    assert!(matches!(result, TransitionResult::Changed));
+    assert!(matches!(
+        &state,
+        AppState::Configuring { validation_errors, .. }
+            if *validation_errors == [ConfigError::MissingQuery, ConfigError::ThresholdOutOfRange(2.0)]
+    ));
+
+    // Same errors, nothing to redraw
+    let result = transition(&mut state, StateEvent::ValidateConfig);
+    assert!(matches!(result, TransitionResult::NoChange));
+
+    // Starting anyway reports the first error
+    let result = transition(&mut state, StateEvent::StartAnalysis);
+    assert!(matches!(
+        result,
+        TransitionResult::Error(message) if message == "Invalid configuration: Query is empty"
+    ));
+
+    if let Some(config) = state.config_mut() {
+        config.query = "test".into();
+        config.threshold = 0.5;
+    }
+    transition(&mut state, StateEvent::ValidateConfig);
+    assert!(matches!(
+        &state,
+        AppState::Configuring { validation_errors, .. } if validation_errors.is_empty()
+    ));
+}
+
 #[test]
 fn test_quit_from_any_state() {
     let mut state = AppState::Configuring {
@@ -38,3 +84,211 @@ fn test_quit_from_any_state() {
     assert!(matches!(result, TransitionResult::Changed));
     assert!(matches!(state, AppState::Exiting));
 }
+
+#[test]
+fn test_cancelled_analysis_shows_partial_results() {
+    let mut state = AppState::Analyzing {
+        config: Config::default(),
+        path: Default::default(),
+        query: "test".into(),
+        progress: Default::default(),
+    };
+
+    // Cancelling alone waits for the background task to report back
+    let result = transition(&mut state, StateEvent::CancelAnalysis);
+    assert!(matches!(result, TransitionResult::NoChange));
+    assert!(matches!(state, AppState::Analyzing { .. }));
+
+    let scored = FileScore {
+        path: "a.txt".into(),
+        score: 0.9,
+        top_chunks: vec![],
+        analysis_duration: None,
+    };
+    let result = transition(
+        &mut state,
+        StateEvent::AnalysisCancelled {
+            results: vec![scored],
+            skipped: vec![],
+            elapsed: std::time::Duration::from_secs(1),
+        },
+    );
+    assert!(matches!(result, TransitionResult::Changed));
+    assert!(matches!(
+        &state,
+        AppState::ViewingResults { results, status: AnalysisStatus::Cancelled, .. }
+            if results.len() == 1
+    ));
+
+    // The flag survives navigating into a file and back
+    transition(&mut state, StateEvent::OpenSelectedFile);
+    transition(&mut state, StateEvent::GoBack);
+    assert!(matches!(
+        state,
+        AppState::ViewingResults {
+            status: AnalysisStatus::Cancelled,
+            ..
+        }
+    ));
+}
+
+fn scored(path: &str, score: f64) -> FileScore {
+    FileScore {
+        path: path.into(),
+        score,
+        top_chunks: vec![],
+        analysis_duration: None,
+    }
+}
+
+fn result_paths(state: &AppState) -> Vec<String> {
+    match state {
+        AppState::ViewingResults { results, .. } => results
+            .iter()
+            .map(|r| r.path.display().to_string())
+            .collect(),
+        _ => panic!("expected ViewingResults, got {:?}", state),
+    }
+}
+
+#[test]
+fn test_streamed_results_are_browsable_while_running() {
+    let mut state = AppState::Analyzing {
+        config: Config {
+            threshold: 0.5,
+            ..Default::default()
+        },
+        path: Default::default(),
+        query: "test".into(),
+        progress: Default::default(),
+    };
+
+    // Files below threshold don't open the results view
+    let result = transition(
+        &mut state,
+        StateEvent::AnalysisResult(scored("low.txt", 0.1)),
+    );
+    assert!(matches!(result, TransitionResult::NoChange));
+
+    // The first match does, while the analysis keeps running
+    transition(&mut state, StateEvent::AnalysisResult(scored("b.txt", 0.7)));
+    assert!(matches!(
+        state,
+        AppState::ViewingResults {
+            status: AnalysisStatus::Running(_),
+            ..
+        }
+    ));
+
+    // Later files are inserted by score, the selection follows its file
+    transition(&mut state, StateEvent::AnalysisResult(scored("a.txt", 0.9)));
+    transition(&mut state, StateEvent::AnalysisResult(scored("c.txt", 0.6)));
+    assert_eq!(result_paths(&state), ["a.txt", "b.txt", "c.txt"]);
+    assert!(matches!(
+        state,
+        AppState::ViewingResults {
+            selected_index: 1,
+            ..
+        }
+    ));
+
+    // Open a file, results and progress keep arriving behind it
+    transition(&mut state, StateEvent::OpenSelectedFile);
+    transition(
+        &mut state,
+        StateEvent::AnalysisResult(scored("d.txt", 0.95)),
+    );
+    let progress = Progress {
+        files_done: 5,
+        total_files: 6,
+        ..Default::default()
+    };
+    transition(&mut state, StateEvent::AnalysisProgress(progress.clone()));
+    assert!(matches!(state, AppState::ViewingFileDetail { .. }));
+
+    transition(&mut state, StateEvent::GoBack);
+    assert_eq!(result_paths(&state), ["d.txt", "a.txt", "b.txt", "c.txt"]);
+    assert!(matches!(
+        &state,
+        AppState::ViewingResults {
+            selected_index: 2,
+            status: AnalysisStatus::Running(p),
+            ..
+        } if *p == progress
+    ));
+
+    // Completion swaps in the final list and keeps the selected file
+    transition(
+        &mut state,
+        StateEvent::AnalysisComplete {
+            results: vec![
+                scored("a.txt", 0.9),
+                scored("b.txt", 0.7),
+                scored("c.txt", 0.6),
+                scored("d.txt", 0.95),
+                scored("low.txt", 0.1),
+            ],
+            skipped: vec![SkippedFile {
+                path: "image.png".into(),
+                reason: "binary",
+                message: "Binary file detected".into(),
+            }],
+            elapsed: std::time::Duration::from_secs(1),
+        },
+    );
+    assert_eq!(result_paths(&state), ["d.txt", "a.txt", "b.txt", "c.txt"]);
+    assert!(matches!(
+        &state,
+        AppState::ViewingResults {
+            selected_index: 2,
+            status: AnalysisStatus::Complete,
+            total_duration: Some(_),
+            skipped,
+            ..
+        } if skipped.len() == 1 && skipped[0].reason == "binary"
+    ));
+
+    // Nothing is streamed into a finished list
+    let result = transition(&mut state, StateEvent::AnalysisResult(scored("e.txt", 1.0)));
+    assert!(matches!(result, TransitionResult::NoChange));
+}
+
+#[test]
+fn test_export_outcome_is_shown_with_the_results() {
+    let mut state = AppState::Analyzing {
+        config: Config::default(),
+        path: Default::default(),
+        query: "test".into(),
+        progress: Default::default(),
+    };
+    transition(
+        &mut state,
+        StateEvent::AnalysisComplete {
+            results: vec![scored("a.txt", 0.9), scored("b.txt", 0.85)],
+            skipped: vec![],
+            elapsed: std::time::Duration::from_secs(1),
+        },
+    );
+
+    // The app writes the report, the state machine doesn't touch the file system
+    let result = transition(&mut state, StateEvent::ExportResults);
+    assert!(matches!(result, TransitionResult::NoChange));
+
+    let path = std::path::PathBuf::from("export.csv");
+    let result = transition(&mut state, StateEvent::ExportFinished(Ok(path.clone())));
+    assert!(matches!(result, TransitionResult::Changed));
+    assert!(matches!(
+        &state,
+        AppState::ViewingResults { last_export: Some(Ok(p)), .. } if *p == path
+    ));
+
+    // Failures are shown in the view, the results stay
+    transition(
+        &mut state,
+        StateEvent::ExportFinished(Err("permission denied".into())),
+    );
+    assert!(matches!(
+        &state,
+        AppState::ViewingResults { last_export: Some(Err(_)), results, .. } if results.len() == 2
+    ));
+}
@@ -2,8 +2,12 @@
 //
 // See docs/copilot/state-machine.md for transition patterns
 
-use super::{AppState, SortMode, StateEvent};
+use super::{AnalysisStatus, AppState, SortMode, StateEvent};
+use crate::analysis::{FileScore, Progress, SkippedFile};
+use crate::config::Config;
+use std::cmp::Ordering;
 use std::path::Path;
+use std::time::Duration;
 
 /// Open the file location in the system's default file manager
 /// Uses the opener crate for cross-platform support (Windows, macOS, Linux)
@@ -25,9 +29,38 @@ pub enum TransitionResult {
     Error(String),
 }
 
+/// Sort by score (descending - highest first)
+fn by_score(a: &FileScore, b: &FileScore) -> Ordering {
+    b.score.partial_cmp(&a.score).unwrap_or(Ordering::Equal)
+}
+
+/// Build the ViewingResults state for the first results of an analysis
+fn results_state(
+    config: &Config,
+    mut results: Vec<FileScore>,
+    total_duration: Option<Duration>,
+    status: AnalysisStatus,
+    skipped: Vec<SkippedFile>,
+) -> AppState {
+    results.retain(|r| r.is_match(config.threshold));
+    results.sort_by(by_score);
+
+    AppState::ViewingResults {
+        config: config.clone(),
+        results,
+        selected_index: 0,
+        sort_mode: SortMode::ByScore,
+        filter: None,
+        total_duration,
+        status,
+        skipped,
+        last_export: None,
+    }
+}
+
 /// Main transition function that handles state changes based on events
 pub fn transition(current_state: &mut AppState, event: StateEvent) -> TransitionResult {
-    let new_state = match (&*current_state, event) {
+    let new_state = match (&mut *current_state, event) {
         // Handle file walk completion in Configuring state
         (
             AppState::Configuring {
@@ -42,10 +75,34 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
             config: config.clone(),
             validation_errors: validation_errors.clone(),
             walk_result: Some(walk_result),
-            autocomplete_available: autocomplete_available.clone(),
+            autocomplete_available: *autocomplete_available,
             autocomplete_suggestion: autocomplete_suggestion.clone(),
         },
 
+        // Re-check the config after an edit, errors are shown next to their field
+        (
+            AppState::Configuring {
+                config,
+                validation_errors,
+                walk_result,
+                autocomplete_available,
+                autocomplete_suggestion,
+            },
+            StateEvent::ValidateConfig,
+        ) => {
+            let errors = config.validation_errors();
+            if errors == *validation_errors {
+                return TransitionResult::NoChange;
+            }
+            AppState::Configuring {
+                config: config.clone(),
+                validation_errors: errors,
+                walk_result: walk_result.clone(),
+                autocomplete_available: *autocomplete_available,
+                autocomplete_suggestion: autocomplete_suggestion.clone(),
+            }
+        }
+
         // Configuration -> Analyzing
         (
             AppState::Configuring {
@@ -56,8 +113,8 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
             StateEvent::StartAnalysis,
         ) => {
             // Validate config before transitioning
-            if let Err(_) = config.validate() {
-                return TransitionResult::Error("Invalid configuration".into());
+            if let Err(e) = config.validate() {
+                return TransitionResult::Error(format!("Invalid configuration: {}", e));
             }
 
             // Ensure we have walk results before starting analysis
@@ -71,8 +128,10 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                 config: config.clone(),
                 path: config.search_path.clone(),
                 query: config.query.clone(),
-                files_processed: 0,
-                total_files: walk_result.files.len(),
+                progress: Progress {
+                    total_files: walk_result.files.len(),
+                    ..Default::default()
+                },
             }
         }
 
@@ -80,30 +139,160 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
         (
             AppState::Analyzing { config, .. },
             StateEvent::AnalysisComplete {
-                mut results,
+                results,
+                skipped,
                 elapsed,
             },
+        ) => results_state(
+            config,
+            results,
+            Some(elapsed),
+            AnalysisStatus::Complete,
+            skipped,
+        ),
+
+        // Analyzing -> ViewingResults as soon as the first matching file is scored,
+        // the analysis continues in the background
+        (
+            AppState::Analyzing {
+                config, progress, ..
+            },
+            StateEvent::AnalysisResult(result),
+        ) => {
+            if !result.is_match(config.threshold) {
+                return TransitionResult::NoChange;
+            }
+            results_state(
+                config,
+                vec![result],
+                None,
+                AnalysisStatus::Running(progress.clone()),
+                vec![],
+            )
+        }
+
+        // The app stops the background task, its results arrive as AnalysisCancelled
+        (
+            AppState::Analyzing { .. } | AppState::ViewingResults { .. },
+            StateEvent::CancelAnalysis,
         ) => {
-            // Filter out results below threshold
-            results.retain(|r| r.score >= config.threshold);
+            return TransitionResult::NoChange;
+        }
 
-            // Sort by score (descending - highest first)
-            results.sort_by(|a, b| {
-                b.score
-                    .partial_cmp(&a.score)
-                    .unwrap_or(std::cmp::Ordering::Equal)
-            });
+        // Analyzing -> ViewingResults with the files scored before cancelling
+        (
+            AppState::Analyzing { config, .. },
+            StateEvent::AnalysisCancelled {
+                results,
+                skipped,
+                elapsed,
+            },
+        ) => results_state(
+            config,
+            results,
+            Some(elapsed),
+            AnalysisStatus::Cancelled,
+            skipped,
+        ),
 
+        // Insert streamed results into the live-sorted list, in place as it can be long
+        (
             AppState::ViewingResults {
-                config: config.clone(),
+                config,
+                results,
+                selected_index,
+                status: AnalysisStatus::Running(_),
+                ..
+            },
+            StateEvent::AnalysisResult(result),
+        ) => {
+            if !result.is_match(config.threshold) {
+                return TransitionResult::NoChange;
+            }
+
+            let index = results.partition_point(|r| by_score(r, &result) != Ordering::Greater);
+            results.insert(index, result);
+
+            // Keep the selected file selected while rows are inserted above it
+            if results.len() > 1 && index <= *selected_index {
+                *selected_index += 1;
+            }
+            return TransitionResult::Changed;
+        }
+
+        // Progress updates while browsing streamed results
+        (
+            AppState::ViewingResults {
+                status: status @ AnalysisStatus::Running(_),
+                ..
+            },
+            StateEvent::AnalysisProgress(progress),
+        ) => {
+            *status = AnalysisStatus::Running(progress);
+            return TransitionResult::Changed;
+        }
+
+        // Background analysis finished (or was cancelled) while browsing
+        (
+            AppState::ViewingResults {
+                config,
                 results,
-                selected_index: 0,
-                sort_mode: SortMode::ByScore,
-                filter: None,
+                selected_index,
+                sort_mode,
+                filter,
+                status: AnalysisStatus::Running(_),
+                last_export,
+                ..
+            },
+            event @ (StateEvent::AnalysisComplete { .. } | StateEvent::AnalysisCancelled { .. }),
+        ) => {
+            let (mut final_results, skipped, elapsed, status) = match event {
+                StateEvent::AnalysisComplete {
+                    results,
+                    skipped,
+                    elapsed,
+                } => (results, skipped, elapsed, AnalysisStatus::Complete),
+                StateEvent::AnalysisCancelled {
+                    results,
+                    skipped,
+                    elapsed,
+                } => (results, skipped, elapsed, AnalysisStatus::Cancelled),
+                _ => unreachable!(),
+            };
+            final_results.retain(|r| r.is_match(config.threshold));
+            final_results.sort_by(by_score);
+
+            // The final list has the same files, keep the selection on the same one
+            let selected_path = results.get(*selected_index).map(|r| &r.path);
+            let selected_index = final_results
+                .iter()
+                .position(|r| Some(&r.path) == selected_path)
+                .unwrap_or(0);
+
+            AppState::ViewingResults {
+                config: config.clone(),
+                results: final_results,
+                selected_index,
+                sort_mode: *sort_mode,
+                filter: filter.clone(),
                 total_duration: Some(elapsed),
+                status,
+                skipped,
+                last_export: last_export.clone(),
             }
         }
 
+        // Analysis events while a file is open update the results to return to
+        (
+            AppState::ViewingFileDetail {
+                previous_results, ..
+            },
+            event @ (StateEvent::AnalysisResult(_)
+            | StateEvent::AnalysisProgress(_)
+            | StateEvent::AnalysisComplete { .. }
+            | StateEvent::AnalysisCancelled { .. }),
+        ) => return transition(previous_results, event),
+
         // Analyzing -> Error
         (AppState::Analyzing { .. }, StateEvent::AnalysisError(msg)) => AppState::Error {
             message: msg,
@@ -118,15 +307,14 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                 query,
                 ..
             },
-            StateEvent::AnalysisProgress { files_done, total },
+            StateEvent::AnalysisProgress(progress),
         ) => {
             // Create new state with updated progress
             AppState::Analyzing {
                 config: config.clone(),
                 path: path.clone(),
                 query: query.clone(),
-                files_processed: files_done,
-                total_files: total,
+                progress,
             }
         }
 
@@ -139,6 +327,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                 sort_mode,
                 filter,
                 total_duration,
+                status,
+                skipped,
+                last_export,
             },
             StateEvent::OpenSelectedFile,
         ) => {
@@ -151,6 +342,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                     sort_mode: *sort_mode,
                     filter: filter.clone(),
                     total_duration: *total_duration,
+                    status: status.clone(),
+                    skipped: skipped.clone(),
+                    last_export: last_export.clone(),
                 });
 
                 AppState::ViewingFileDetail {
@@ -172,6 +366,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                 sort_mode,
                 filter,
                 total_duration,
+                status,
+                skipped,
+                last_export,
                 ..
             },
             StateEvent::SelectFile(index),
@@ -184,6 +381,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                     sort_mode: *sort_mode,
                     filter: filter.clone(),
                     total_duration: *total_duration,
+                    status: status.clone(),
+                    skipped: skipped.clone(),
+                    last_export: last_export.clone(),
                 }
             } else {
                 return TransitionResult::Error("Invalid file index".into());
@@ -198,6 +398,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                 selected_index,
                 filter,
                 total_duration,
+                status,
+                skipped,
+                last_export,
                 ..
             },
             StateEvent::ChangeSortMode(new_mode),
@@ -208,6 +411,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
             sort_mode: new_mode,
             filter: filter.clone(),
             total_duration: *total_duration,
+            status: status.clone(),
+            skipped: skipped.clone(),
+            last_export: last_export.clone(),
         },
 
         // Filter changes within ViewingResults
@@ -218,6 +424,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
                 selected_index,
                 sort_mode,
                 total_duration,
+                status,
+                skipped,
+                last_export,
                 ..
             },
             StateEvent::SetFilter(new_filter),
@@ -228,6 +437,9 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
             sort_mode: *sort_mode,
             filter: new_filter,
             total_duration: *total_duration,
+            status: status.clone(),
+            skipped: skipped.clone(),
+            last_export: last_export.clone(),
         },
 
         // Scrolling within ViewingFileDetail
@@ -287,10 +499,15 @@ pub fn transition(current_state: &mut AppState, event: StateEvent) -> Transition
             config: config.clone(),
             path: config.search_path.clone(),
             query: config.query.clone(),
-            files_processed: 0,
-            total_files: 0,
+            progress: Progress::default(),
         },
 
+        // The app writes the report, the outcome is shown in the actions bar
+        (AppState::ViewingResults { last_export, .. }, StateEvent::ExportFinished(outcome)) => {
+            *last_export = Some(outcome);
+            return TransitionResult::Changed;
+        }
+
         // Open file location in Explorer (ViewingResults)
         (
             AppState::ViewingResults {
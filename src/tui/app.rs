@@ -5,10 +5,12 @@
 use crossterm::event::{self, Event, KeyCode};
 use ratatui::{Terminal, backend::CrosstermBackend};
 use std::io;
+use std::path::PathBuf;
 use tokio::sync::mpsc;
 
 use super::super::state_machine::handlers::get_handler_for_state;
 use super::{Dashboard, FocusManager, focus::FocusDirection};
+use crate::presentation::report::{DEFAULT_REPORT_FILE, Report, write_report};
 use crate::state_machine::{AppState, StateEvent, StateMachine};
 use crate::{analysis, file_walker};
 
@@ -22,6 +24,8 @@ pub struct App {
     analysis_event_tx: mpsc::UnboundedSender<StateEvent>,
     walker_event_rx: mpsc::UnboundedReceiver<StateEvent>,
     walker_event_tx: mpsc::UnboundedSender<StateEvent>,
+    // Stops the running analysis task (if any) on Esc
+    analysis_cancel: Option<analysis::CancellationToken>,
 }
 
 impl App {
@@ -43,6 +47,7 @@ impl App {
             analysis_event_tx: tx_analysis,
             walker_event_rx: rx_walker,
             walker_event_tx: tx_walker,
+            analysis_cancel: None,
         }
     }
 
@@ -51,6 +56,7 @@ impl App {
         loop {
             // Check if we should quit
             if self.should_quit || matches!(self.state_machine.current_state(), AppState::Exiting) {
+                self.stop_analysis();
                 break;
             }
 
@@ -82,8 +88,11 @@ impl App {
 
             // Check for analysis events from background task
             while let Ok(event) = self.analysis_event_rx.try_recv() {
-                let result = self.state_machine.process_event(event);
-                if matches!(result, crate::state_machine::TransitionResult::Changed) {
+                let previous = std::mem::discriminant(self.state_machine.current_state());
+                self.state_machine.process_event(event);
+                // Streamed results and progress arrive while the user browses,
+                // only reset focus when moving to another view
+                if std::mem::discriminant(self.state_machine.current_state()) != previous {
                     self.needs_clear = true;
                     self.focus_manager =
                         FocusManager::new_for_state(self.state_machine.current_state());
@@ -91,10 +100,10 @@ impl App {
             }
 
             // Handle input
-            if event::poll(std::time::Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    self.handle_key(key);
-                }
+            if event::poll(std::time::Duration::from_millis(100))?
+                && let Event::Key(key) = event::read()?
+            {
+                self.handle_key(key);
             }
         }
 
@@ -108,6 +117,16 @@ impl App {
             return;
         }
 
+        self.handle_key_press(key);
+
+        // Inputs edit the config in place, re-check it after every key.
+        // Same view, so focus stays where the user is typing
+        if matches!(self.current_state(), AppState::Configuring { .. }) {
+            self.state_machine.process_event(StateEvent::ValidateConfig);
+        }
+    }
+
+    fn handle_key_press(&mut self, key: crossterm::event::KeyEvent) {
         // Global shortcuts that work in any state
         match key.code {
             KeyCode::Char('q') | KeyCode::Char('c')
@@ -146,21 +165,19 @@ impl App {
                             autocomplete_suggestion,
                             ..
                         } = self.current_state_mut()
+                            && *autocomplete_available
+                            && let Some(suggestion) = autocomplete_suggestion.clone()
                         {
-                            if *autocomplete_available {
-                                if let Some(suggestion) = autocomplete_suggestion.clone() {
-                                    config.search_path = std::path::PathBuf::from(&suggestion);
-                                    *autocomplete_available = false;
-                                    *autocomplete_suggestion = None;
-
-                                    // Trigger file walker for new path
-                                    let config_clone = config.clone();
-                                    let tx_clone = self.walker_event_tx.clone();
-                                    tokio::spawn(async move {
-                                        Self::run_filewalker_task(config_clone, tx_clone).await;
-                                    });
-                                }
-                            }
+                            config.search_path = std::path::PathBuf::from(&suggestion);
+                            *autocomplete_available = false;
+                            *autocomplete_suggestion = None;
+
+                            // Trigger file walker for new path
+                            let config_clone = config.clone();
+                            let tx_clone = self.walker_event_tx.clone();
+                            tokio::spawn(async move {
+                                Self::run_filewalker_task(config_clone, tx_clone).await;
+                            });
                         }
                         return;
                     }
@@ -205,6 +222,26 @@ impl App {
                                 return;
                             }
                         }
+                        Focus::OptionsPanel if c == 'a' => {
+                            if let AppState::Configuring { config, .. } = self.current_state_mut() {
+                                config.aggregation = config.aggregation.next();
+                                return;
+                            }
+                        }
+                        Focus::OptionsPanel if c == '+' || c == '-' => {
+                            if let AppState::Configuring { config, .. } = self.current_state_mut() {
+                                // 0 means all cores, so "-" from 1 goes back to "All"
+                                let cores = std::thread::available_parallelism()
+                                    .map(|n| n.get())
+                                    .unwrap_or(1);
+                                config.num_threads = if c == '+' {
+                                    (config.num_threads + 1).min(cores)
+                                } else {
+                                    config.num_threads.saturating_sub(1)
+                                };
+                                return;
+                            }
+                        }
                         _ => {} // Not in an input field
                     }
                 }
@@ -263,26 +300,51 @@ impl App {
                 break;
             }
 
+            // Reports are written here, the state machine only records the outcome
+            let event = match event {
+                StateEvent::ExportResults => match self.export_results() {
+                    Some(outcome) => StateEvent::ExportFinished(outcome),
+                    None => continue,
+                },
+                event => event,
+            };
+
             // If StartAnalysis or Reanalyze event, spawn background task
             if matches!(event, StateEvent::StartAnalysis) {
+                // Only start analysis if we have walk results
                 if let AppState::Configuring {
                     config,
-                    walk_result,
+                    walk_result: Some(walk_result),
                     ..
                 } = self.state_machine.current_state()
                 {
-                    // Only start analysis if we have walk results
-                    if let Some(walk_result) = walk_result {
-                        let config_clone = config.clone();
-                        let walk_result_clone = walk_result.clone();
-                        let tx_clone = self.analysis_event_tx.clone();
-                        tokio::spawn(async move {
-                            Self::run_analysis_task(config_clone, walk_result_clone, tx_clone)
-                                .await;
-                        });
-                    }
+                    let config_clone = config.clone();
+                    let walk_result_clone = walk_result.clone();
+                    let cancel = analysis::CancellationToken::new();
+                    self.stop_analysis();
+                    self.analysis_cancel = Some(cancel.clone());
+                    let tx_clone = self.analysis_event_tx.clone();
+                    tokio::spawn(async move {
+                        Self::run_analysis_task(config_clone, walk_result_clone, tx_clone, cancel)
+                            .await;
+                    });
+                }
+            } else if matches!(event, StateEvent::CancelAnalysis) {
+                // The task stops and reports what it scored so far
+                if let Some(cancel) = self.analysis_cancel.take() {
+                    cancel.cancel();
                 }
-            } else if matches!(event, StateEvent::Reanalyze) {
+            } else if matches!(event, StateEvent::Reanalyze | StateEvent::GoBack)
+                && matches!(
+                    self.state_machine.current_state(),
+                    AppState::ViewingResults { .. }
+                )
+            {
+                // Leaving the results, a still running analysis is no longer wanted
+                self.stop_analysis();
+            }
+
+            if matches!(event, StateEvent::Reanalyze) {
                 // For reanalyze, we need to trigger a new file walk first
                 if let Some(config) = self.state_machine.current_state().config() {
                     let config_clone = config.clone();
@@ -308,6 +370,45 @@ impl App {
         }
     }
 
+    /// Cancel the running analysis (if any) and ignore everything it still sends
+    fn stop_analysis(&mut self) {
+        if let Some(cancel) = self.analysis_cancel.take() {
+            cancel.cancel();
+            let (tx, rx) = mpsc::unbounded_channel();
+            self.analysis_event_tx = tx;
+            self.analysis_event_rx = rx;
+        }
+    }
+
+    /// Write the results list to the --output file (or the default report file)
+    fn export_results(&self) -> Option<Result<PathBuf, String>> {
+        let AppState::ViewingResults {
+            config,
+            results,
+            total_duration,
+            skipped,
+            ..
+        } = self.current_state()
+        else {
+            return None;
+        };
+
+        let path = config
+            .output_file
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_REPORT_FILE));
+        let report = Report {
+            scores: results,
+            skipped,
+            config,
+            elapsed: *total_duration,
+        };
+        Some(match write_report(&path, &report) {
+            Ok(()) => Ok(path),
+            Err(e) => Err(e.to_string()),
+        })
+    }
+
     /// Get a reference to the current state
     pub fn current_state(&self) -> &AppState {
         self.state_machine.current_state()
@@ -405,15 +506,16 @@ impl App {
         config: crate::config::Config,
         walk_result: file_walker::WalkResult,
         tx: mpsc::UnboundedSender<StateEvent>,
+        cancel: analysis::CancellationToken,
     ) {
         // Start tracking elapsed time
         let start_time = std::time::Instant::now();
 
         // Send initial progress update with total file count
-        let _ = tx.send(StateEvent::AnalysisProgress {
-            files_done: 0,
-            total: walk_result.files.len(),
-        });
+        let _ = tx.send(StateEvent::AnalysisProgress(analysis::Progress {
+            total_files: walk_result.files.len(),
+            ..Default::default()
+        }));
 
         // If no files found, send error
         if walk_result.files.is_empty() {
@@ -426,8 +528,22 @@ impl App {
         }
 
         // Perform analysis using blocking task to avoid blocking tokio runtime
+        let hooks_cancel = cancel.clone();
+        let hooks_tx = tx.clone();
         let analysis_result = tokio::task::spawn_blocking(move || {
-            analysis::analyse_files(&walk_result.files, &config)
+            let on_progress = |progress: &analysis::Progress| {
+                let _ = hooks_tx.send(StateEvent::AnalysisProgress(progress.clone()));
+            };
+            // Stream every file as it is scored, the results view opens on the first match
+            let on_result = |result: &analysis::FileScore| {
+                let _ = hooks_tx.send(StateEvent::AnalysisResult(result.clone()));
+            };
+            let hooks = analysis::AnalysisHooks {
+                cancel: hooks_cancel,
+                on_progress: Some(&on_progress),
+                on_result: Some(&on_result),
+            };
+            analysis::analyse_files_with(&walk_result.files, &config, &hooks)
         })
         .await;
 
@@ -435,9 +551,20 @@ impl App {
         let elapsed = start_time.elapsed();
 
         match analysis_result {
-            Ok(Ok(results)) => {
+            Ok(Ok(report)) if cancel.is_cancelled() => {
+                let _ = tx.send(StateEvent::AnalysisCancelled {
+                    skipped: report.skipped(),
+                    results: report.scores,
+                    elapsed,
+                });
+            }
+            Ok(Ok(report)) => {
                 // Send completion event with elapsed time
-                let _ = tx.send(StateEvent::AnalysisComplete { results, elapsed });
+                let _ = tx.send(StateEvent::AnalysisComplete {
+                    skipped: report.skipped(),
+                    results: report.scores,
+                    elapsed,
+                });
             }
             Ok(Err(e)) => {
                 let _ = tx.send(StateEvent::AnalysisError(format!("Analysis failed: {}", e)));
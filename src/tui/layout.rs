@@ -46,7 +46,7 @@ impl LayoutConfig {
                 Constraint::Length(3),  // Path input
                 Constraint::Length(3),  // Query input
                 Constraint::Min(7),    // Found files
-                Constraint::Length(9),  // Options
+                Constraint::Length(10), // Options
                 Constraint::Length(3),  // Start button
             ],
         }
@@ -109,6 +109,21 @@ pub fn results_two_column(area: Rect) -> (Rect, Rect) {
     (chunks[0], chunks[1])
 }
 
+/// Split the file list column, leaving room below for skipped files (if any)
+pub fn file_list_split(area: Rect, skipped: usize) -> (Rect, Rect) {
+    let height = if skipped == 0 {
+        0
+    } else {
+        skipped.min(6) as u16 + 2 // Borders
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(height)])
+        .split(area);
+
+    (chunks[0], chunks[1])
+}
+
 /// Create a right panel split (preview, stats, actions)
 pub fn right_panel_split(area: Rect) -> (Rect, Rect, Rect) {
     let chunks = Layout::default()
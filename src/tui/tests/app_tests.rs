@@ -95,6 +95,38 @@ fn test_query_input() {
     }
 }
 
+#[test]
+fn test_validation_errors_update_while_typing() {
+    use crate::errors::ConfigError;
+
+    let initial_state = AppState::Configuring {
+        config: Config {
+            search_path: "testdata".into(),
+            ..Default::default()
+        },
+        validation_errors: vec![],
+        walk_result: None,
+        autocomplete_available: false,
+        autocomplete_suggestion: None,
+    };
+    let mut app = App::new(initial_state);
+    app.focus_manager.move_focus(FocusDirection::Next);
+
+    let errors = |app: &App| match app.current_state() {
+        AppState::Configuring {
+            validation_errors, ..
+        } => validation_errors.clone(),
+        state => panic!("Expected Configuring state, got {:?}", state),
+    };
+
+    // Typing then deleting the query brings the error back, focus stays put
+    app.handle_key(KeyEvent::from(KeyCode::Char('t')));
+    assert!(errors(&app).is_empty());
+    app.handle_key(KeyEvent::from(KeyCode::Backspace));
+    assert_eq!(errors(&app), [ConfigError::MissingQuery]);
+    assert_eq!(app.focus_manager.current(), crate::tui::focus::Focus::QueryInput);
+}
+
 #[tokio::test]
 async fn test_backspace_in_input() {
     let initial_state = AppState::Configuring {
@@ -154,3 +186,48 @@ async fn test_key_release_events_ignored() {
         assert_eq!(config.search_path.to_string_lossy(), "x");
     }
 }
+
+#[tokio::test]
+async fn test_ctrl_e_writes_report_and_shows_outcome() {
+    use crate::state_machine::{AnalysisStatus, SortMode};
+    use crossterm::event::KeyModifiers;
+
+    let results_app = |output_file| {
+        App::new(AppState::ViewingResults {
+            config: Config {
+                output_file: Some(output_file),
+                ..Default::default()
+            },
+            results: vec![],
+            selected_index: 0,
+            sort_mode: SortMode::ByScore,
+            filter: None,
+            total_duration: None,
+            status: AnalysisStatus::Complete,
+            skipped: vec![],
+            last_export: None,
+        })
+    };
+    let ctrl_e = KeyEvent::new(KeyCode::Char('e'), KeyModifiers::CONTROL);
+    let dir = tempfile::tempdir().unwrap();
+
+    let path = dir.path().join("export.csv");
+    let mut app = results_app(path.clone());
+    app.handle_key(ctrl_e);
+    assert!(matches!(
+        app.current_state(),
+        AppState::ViewingResults { last_export: Some(Ok(p)), .. } if *p == path
+    ));
+    assert!(std::fs::read_to_string(&path).unwrap().starts_with("path,"));
+
+    // Write errors end up in the view instead of aborting the app
+    let mut app = results_app(dir.path().join("missing_dir/export.csv"));
+    app.handle_key(ctrl_e);
+    assert!(matches!(
+        app.current_state(),
+        AppState::ViewingResults {
+            last_export: Some(Err(_)),
+            ..
+        }
+    ));
+}
@@ -2,18 +2,52 @@
 //
 // See docs/copilot/ui.md for widget patterns
 
+use std::path::{Path, PathBuf};
+
 use ratatui::{
     Frame,
-    layout::{Position, Rect},
+    layout::{Constraint, Direction, Layout, Position, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
-    widgets::{Block, BorderType, Borders, List, ListItem, Padding, Paragraph},
+    widgets::{Block, BorderType, Borders, Gauge, List, ListItem, Padding, Paragraph},
 };
 
 use super::focus::{Focus, FocusManager};
-use super::layout::{LayoutConfig, results_two_column, right_panel_split};
-use crate::analysis::FileScore;
-use crate::state_machine::AppState;
+use super::layout::{LayoutConfig, file_list_split, results_two_column, right_panel_split};
+use crate::analysis::{FileScore, Progress, SkippedFile};
+use crate::config::ConfigField;
+use crate::errors::ConfigError;
+use crate::presentation::{format_position, format_term_scores, report::DEFAULT_REPORT_FILE};
+use crate::state_machine::{AnalysisStatus, AppState};
+
+/// Human readable byte count (1024 based)
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// First validation error about one of `fields`
+fn field_error<'a>(errors: &'a [ConfigError], fields: &[ConfigField]) -> Option<&'a ConfigError> {
+    errors.iter().find(|e| fields.contains(&e.field()))
+}
+
+/// Red bottom title of an input holding its error, empty if valid
+fn error_title(error: Option<&ConfigError>) -> Line<'static> {
+    match error {
+        Some(e) => Line::from(format!(" ⚠ {} ", e)).style(Style::default().fg(Color::Red)),
+        None => Line::default(),
+    }
+}
 
 /// Helper to build highlighted text lines with matched character indices.
 /// Returns a vector of Lines with proper highlighting and text wrapping.
@@ -35,6 +69,13 @@ fn build_highlighted_lines(
         // Build spans for this line with highlighting
         let spans = match indices {
             Some(idx_vec) if !idx_vec.is_empty() => {
+                // Indices are char positions, the walk below uses byte offsets
+                let highlighted: std::collections::HashSet<usize> = text
+                    .char_indices()
+                    .enumerate()
+                    .filter(|(i, _)| idx_vec.contains(i))
+                    .map(|(_, (byte, _))| byte)
+                    .collect();
                 let mut spans = Vec::new();
                 let mut current_text = String::new();
                 let mut is_highlighted = false;
@@ -51,7 +92,7 @@ fn build_highlighted_lines(
                     }
 
                     let global_i = found_at.unwrap_or(char_offset);
-                    let should_highlight = idx_vec.contains(&global_i);
+                    let should_highlight = highlighted.contains(&global_i);
 
                     if should_highlight != is_highlighted {
                         // Flush current span if it has content
@@ -163,9 +204,21 @@ impl Dashboard {
                 results,
                 selected_index,
                 total_duration,
+                status,
+                skipped,
+                last_export,
                 ..
             } => {
-                self.render_results(frame, results, *selected_index, focus, *total_duration);
+                self.render_results(
+                    frame,
+                    results,
+                    *selected_index,
+                    focus,
+                    *total_duration,
+                    status,
+                    skipped,
+                    last_export.as_ref(),
+                );
             }
             AppState::ViewingFileDetail {
                 file_result,
@@ -175,12 +228,9 @@ impl Dashboard {
                 self.render_file_detail(frame, file_result, *scroll_position, focus);
             }
             AppState::Analyzing {
-                files_processed,
-                total_files,
-                query,
-                ..
+                progress, query, ..
             } => {
-                self.render_analyzing(frame, *files_processed, *total_files, query);
+                self.render_analyzing(frame, progress, query);
             }
             AppState::Error { message, .. } => {
                 self.render_error(frame, message);
@@ -191,11 +241,12 @@ impl Dashboard {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_configuring(
         &self,
         frame: &mut Frame,
         config: &crate::config::Config,
-        validation_errors: &[String],
+        validation_errors: &[ConfigError],
         walk_result: &Option<crate::file_walker::WalkResult>,
         autocomplete_available: &bool,
         autocomplete_suggestion: &Option<String>,
@@ -204,7 +255,7 @@ impl Dashboard {
         let chunks = self.layout.split(frame.area());
 
         // Path input - render directly from config
-        if let Some(&area) = chunks.get(0) {
+        if let Some(&area) = chunks.first() {
             let is_focused = focus.is_focused(Focus::PathInput);
             let displayed_path: Text = if *autocomplete_available {
                 if let Some(suggestion) = autocomplete_suggestion {
@@ -230,6 +281,10 @@ impl Dashboard {
             } else {
                 Text::from(config.search_path.to_string_lossy().to_string())
             };
+            let path_error = field_error(
+                validation_errors,
+                &[ConfigField::SearchPath, ConfigField::MaxSearchDepth],
+            );
             let path_widget = Paragraph::new(displayed_path)
                 .style(if config.search_path.exists() {
                     Style::default().fg(Color::Green)
@@ -240,6 +295,7 @@ impl Dashboard {
                     Block::default()
                         .borders(Borders::ALL)
                         .title(" Search Path (editable) ")
+                        .title_bottom(error_title(path_error))
                         .border_type(if is_focused {
                             BorderType::Double
                         } else {
@@ -259,8 +315,12 @@ impl Dashboard {
         // Query input - render directly from config
         if let Some(&area) = chunks.get(1) {
             let is_focused = focus.is_focused(Focus::QueryInput);
+            let query_error = field_error(
+                validation_errors,
+                &[ConfigField::Query, ConfigField::ReferenceFile],
+            );
             let query_widget = Paragraph::new(config.query.as_str())
-                .style(if !config.query.is_empty() {
+                .style(if !config.query.is_empty() && query_error.is_none() {
                     Style::default().fg(Color::Green)
                 } else {
                     Style::default().fg(Color::Red)
@@ -269,6 +329,7 @@ impl Dashboard {
                     Block::default()
                         .borders(Borders::ALL)
                         .title(" Query (editable) ")
+                        .title_bottom(error_title(query_error))
                         .border_type(if is_focused {
                             BorderType::Double
                         } else {
@@ -330,56 +391,91 @@ impl Dashboard {
             }
         }
 
-        // Options panel (read-only display) or validation errors
+        // Options panel (aggregation is editable, the rest read-only),
+        // invalid values are followed by their error
         if let Some(&area) = chunks.get(3) {
             let is_focused = focus.is_focused(Focus::OptionsPanel);
 
-            // Show validation errors OR options
-            if !validation_errors.is_empty() {
-                let error_lines: Vec<Line> = validation_errors
-                    .iter()
-                    .map(|e| Line::from(Span::styled(e.clone(), Style::default().fg(Color::Red))))
-                    .collect();
-                let error_widget = Paragraph::new(error_lines).block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title(" Validation Errors ")
-                        .border_style(Style::default().fg(Color::Red)),
-                );
-                frame.render_widget(error_widget, area);
+            let threads = if config.num_threads > 0 {
+                config.num_threads.to_string()
             } else {
-                let options_text = format!(
-                    "- Window Size: {:<15}\n- Max Window: {:<15}\n- Threshold: {:<15.2}\n- Top N: {:<15}\n- Threads: {:<15}\n- File Exts: {:<15}",
-                    config.window_size,
-                    config.max_window_size,
-                    config.threshold,
-                    config.top_n,
-                    if config.num_threads > 0 {
-                        config.num_threads.to_string()
+                "All".into()
+            };
+            let options = [
+                (
+                    "Window Size",
+                    config.window_size.to_string(),
+                    Some(ConfigField::WindowSize),
+                ),
+                (
+                    "Max Window",
+                    config.max_window_size.to_string(),
+                    Some(ConfigField::MaxWindowSize),
+                ),
+                (
+                    "Threshold",
+                    format!("{:.2}", config.threshold),
+                    Some(ConfigField::Threshold),
+                ),
+                ("Top N", config.top_n.to_string(), Some(ConfigField::TopN)),
+                ("Threads", threads, None),
+                (
+                    "File Exts",
+                    config.file_exts.join(", "),
+                    Some(ConfigField::FileExts),
+                ),
+                ("Aggregation", config.aggregation.label().to_string(), None),
+                (
+                    "Output",
+                    config
+                        .output_file
+                        .as_deref()
+                        .unwrap_or(Path::new(DEFAULT_REPORT_FILE))
+                        .display()
+                        .to_string(),
+                    Some(ConfigField::OutputFile),
+                ),
+            ];
+            let mut has_errors = false;
+            let option_lines: Vec<Line> = options
+                .into_iter()
+                .map(|(label, value, field)| {
+                    let mut spans = vec![Span::raw(format!("- {}: {:<15}", label, value))];
+                    if let Some(e) = field.and_then(|f| field_error(validation_errors, &[f])) {
+                        has_errors = true;
+                        spans.push(Span::styled(
+                            format!(" ⚠ {}", e),
+                            Style::default().fg(Color::Red),
+                        ));
+                    }
+                    Line::from(spans)
+                })
+                .collect();
+
+            let options_widget = Paragraph::new(option_lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Options (a: Aggregation, +/-: Threads) ")
+                    .padding(Padding::horizontal(1))
+                    .border_style(if has_errors {
+                        Style::default().fg(Color::Red)
                     } else {
-                        "All".into()
-                    },
-                    config.file_exts.join(", ")
-                );
-                let options_widget = Paragraph::new(options_text).block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title(" Options (Read-Only) ")
-                        .padding(Padding::horizontal(1))
-                        .border_type(if is_focused {
-                            BorderType::Double
-                        } else {
-                            BorderType::Plain
-                        }),
-                );
-                frame.render_widget(options_widget, area);
-            }
+                        Style::default()
+                    })
+                    .border_type(if is_focused {
+                        BorderType::Double
+                    } else {
+                        BorderType::Plain
+                    }),
+            );
+            frame.render_widget(options_widget, area);
         }
 
         // Start button
         if let Some(&area) = chunks.get(4) {
             let is_focused = focus.is_focused(Focus::StartButton);
-            let can_start = !config.query.is_empty()
+            let can_start = validation_errors.is_empty()
+                && (!config.query.is_empty() || config.reference_file.is_some())
                 && config.search_path.exists()
                 && walk_result.is_some()
                 && walk_result
@@ -416,6 +512,7 @@ impl Dashboard {
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn render_results(
         &self,
         frame: &mut Frame,
@@ -423,8 +520,12 @@ impl Dashboard {
         selected_index: usize,
         focus: &FocusManager,
         total_duration: Option<std::time::Duration>,
+        status: &AnalysisStatus,
+        skipped: &[SkippedFile],
+        last_export: Option<&Result<PathBuf, String>>,
     ) {
         let (left, right) = results_two_column(frame.area());
+        let (left, skipped_area) = file_list_split(left, skipped.len());
 
         // File list (left)
         let is_focused = focus.is_focused(Focus::FileList);
@@ -443,8 +544,19 @@ impl Dashboard {
 
                 // Normalize path separators to forward slashes for consistency
                 let normalized_path = result.path.display().to_string().replace('\\', "/");
-                let text = format!("{}{}", prefix, normalized_path);
-                ListItem::new(text).style(style)
+                let mut spans = vec![Span::styled(
+                    format!("{}{}", prefix, normalized_path),
+                    style,
+                )];
+
+                // Heading or code symbol of the best chunk
+                if let Some(location) = result.top_chunks.first().and_then(|c| c.chunk.location()) {
+                    spans.push(Span::styled(
+                        format!("  › {}", location),
+                        Style::default().fg(Color::Cyan),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
@@ -452,7 +564,11 @@ impl Dashboard {
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Files")
+                    .title(match status {
+                        AnalysisStatus::Running(_) => "Files (analysis running, Esc: stop)",
+                        AnalysisStatus::Complete => "Files",
+                        AnalysisStatus::Cancelled => "Files (partial, analysis cancelled)",
+                    })
                     .border_style(if is_focused {
                         Style::default().fg(Color::Yellow)
                     } else {
@@ -475,6 +591,10 @@ impl Dashboard {
 
         frame.render_stateful_widget(file_list, left, &mut list_state);
 
+        if !skipped.is_empty() {
+            self.render_skipped(frame, skipped, skipped_area);
+        }
+
         // Right panel (preview, stats, actions)
         let (preview_area, stats_area, actions_area) = right_panel_split(right);
 
@@ -485,10 +605,10 @@ impl Dashboard {
         }
 
         // Stats
-        self.render_stats(frame, results, stats_area, total_duration);
+        self.render_stats(frame, results, stats_area, total_duration, status);
 
         // Actions
-        self.render_actions(frame, actions_area);
+        self.render_actions(frame, actions_area, last_export);
     }
 
     fn render_file_preview(
@@ -527,12 +647,20 @@ impl Dashboard {
                 ),
                 Span::raw(" "),
                 Span::styled(
-                    format!("[{}..{}]", chunk.chunk.start_byte, chunk.chunk.end_byte),
+                    format_position(&file_result.path, chunk),
                     Style::default().fg(Color::DarkGray),
                 ),
             ]);
             lines.push(match_line);
 
+            // Where in the document the chunk lives (Markdown heading or code symbol)
+            if let Some(location) = chunk.chunk.location() {
+                lines.push(Line::from(vec![
+                    Span::raw("     "),
+                    Span::styled(location.to_string(), Style::default().fg(Color::Cyan)),
+                ]));
+            }
+
             // Context header
             lines.push(Line::from(Span::styled(
                 "Context:",
@@ -576,6 +704,7 @@ impl Dashboard {
         results: &[FileScore],
         area: Rect,
         total_duration: Option<std::time::Duration>,
+        status: &AnalysisStatus,
     ) {
         let matched = results.iter().filter(|r| r.score > 0.0).count();
 
@@ -585,10 +714,27 @@ impl Dashboard {
             "N/A".to_string()
         };
 
+        let duration_line = match status {
+            AnalysisStatus::Running(progress) => vec![Span::styled(
+                format!(
+                    "Analyzing: {} / {} files ({:.0}%)",
+                    progress.files_done,
+                    progress.total_files,
+                    progress.ratio() * 100.0
+                ),
+                Style::default().fg(Color::Yellow),
+            )],
+            AnalysisStatus::Complete => vec![Span::raw(format!("Duration: {}", duration_text))],
+            AnalysisStatus::Cancelled => vec![
+                Span::raw(format!("Duration: {}", duration_text)),
+                Span::styled(" (cancelled)", Style::default().fg(Color::Yellow)),
+            ],
+        };
+
         let lines = vec![
             Line::from(format!("Total files: {}", results.len())),
             Line::from(format!("Matches: {}", matched)),
-            Line::from(format!("Duration: {}", duration_text)),
+            Line::from(duration_line),
         ];
 
         let stats =
@@ -596,13 +742,53 @@ impl Dashboard {
         frame.render_widget(stats, area);
     }
 
-    fn render_actions(&self, frame: &mut Frame, area: Rect) {
-        let lines = vec![
+    fn render_skipped(&self, frame: &mut Frame, skipped: &[SkippedFile], area: Rect) {
+        let reason_width = skipped.iter().map(|s| s.reason.len()).max().unwrap_or(0);
+        let lines: Vec<Line> = skipped
+            .iter()
+            .map(|s| {
+                Line::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", s.reason, width = reason_width),
+                        Style::default().fg(Color::Red),
+                    ),
+                    Span::raw(s.path.display().to_string().replace('\\', "/")),
+                ])
+            })
+            .collect();
+
+        let panel = Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Skipped files ({})", skipped.len())),
+        );
+        frame.render_widget(panel, area);
+    }
+
+    fn render_actions(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        last_export: Option<&Result<PathBuf, String>>,
+    ) {
+        let mut lines = vec![
             Line::from("Ctrl+O: Open Location"),
             Line::from("Ctrl+R: Reanalyze"),
+            Line::from("Ctrl+E: Export"),
             Line::from("Esc: Back"),
             Line::from("Ctrl+Q: Quit"),
         ];
+        match last_export {
+            Some(Ok(path)) => lines.push(Line::styled(
+                format!("Exported to {}", path.display()),
+                Style::default().fg(Color::Green),
+            )),
+            Some(Err(e)) => lines.push(Line::styled(
+                format!("Export failed: {}", e),
+                Style::default().fg(Color::Red),
+            )),
+            None => {}
+        }
 
         let actions =
             Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Actions"));
@@ -618,7 +804,7 @@ impl Dashboard {
     ) {
         let chunks = self.layout.split(frame.area());
 
-        if let Some(&area) = chunks.get(0) {
+        if let Some(&area) = chunks.first() {
             let mut lines = vec![Line::from(Span::styled(
                 format!("File: {}", file_result.path.display()),
                 Style::default()
@@ -693,12 +879,42 @@ impl Dashboard {
                         ),
                         Span::raw(" "),
                         Span::styled(
-                            format!("[{}..{}]", chunk.chunk.start_byte, chunk.chunk.end_byte),
+                            format_position(&file_result.path, chunk),
                             Style::default().fg(Color::DarkGray),
                         ),
                     ]);
                     lines.push(match_line);
 
+                    // Where in the document the chunk lives (Markdown heading or code symbol)
+                    if let Some(location) = chunk.chunk.location() {
+                        lines.push(Line::from(vec![
+                            Span::raw("     "),
+                            Span::styled(location.to_string(), Style::default().fg(Color::Cyan)),
+                        ]));
+                    }
+
+                    // Query by example: show which part of the reference this chunk resembles
+                    if let Some(reference) = &chunk.reference_chunk {
+                        lines.push(Line::from(vec![
+                            Span::raw("     similar to reference "),
+                            Span::styled(
+                                format!("at {}:{}", reference.line, reference.column),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                        ]));
+                    }
+
+                    // Boolean queries: explain the score term by term
+                    if let Some(terms) = &chunk.term_scores {
+                        lines.push(Line::from(vec![
+                            Span::raw("     terms: "),
+                            Span::styled(
+                                format_term_scores(terms),
+                                Style::default().fg(Color::DarkGray),
+                            ),
+                        ]));
+                    }
+
                     // Context header
                     lines.push(Line::from(Span::styled(
                         "Context:",
@@ -732,32 +948,68 @@ impl Dashboard {
         }
     }
 
-    fn render_analyzing(
-        &self,
-        frame: &mut Frame,
-        files_processed: usize,
-        total_files: usize,
-        query: &str,
-    ) {
-        let area = frame.area();
-        let progress_text = if total_files > 0 {
+    fn render_analyzing(&self, frame: &mut Frame, progress: &Progress, query: &str) {
+        let [gauge_area, stats_area] = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(0)])
+            .areas(frame.area());
+
+        let label = if progress.total_files > 0 {
             format!(
-                "Analyzing: {} / {} files ({}%)\nQuery: {}",
-                files_processed,
-                total_files,
-                (files_processed * 100) / total_files,
-                query
+                "{} / {} files ({:.0}%)",
+                progress.files_done,
+                progress.total_files,
+                progress.ratio() * 100.0
             )
         } else {
-            format!("Discovering files...\nQuery: {}", query)
+            "Discovering files...".to_string()
         };
+        let gauge = Gauge::default()
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Analysis in Progress"),
+            )
+            .gauge_style(Style::default().fg(Color::Cyan))
+            .ratio(progress.ratio())
+            .label(label);
+        frame.render_widget(gauge, gauge_area);
+
+        let eta_text = match progress.eta() {
+            Some(eta) if progress.files_done > 0 => format!("{:.0}s", eta.as_secs_f64().ceil()),
+            _ => "N/A".to_string(),
+        };
+        let current_file = progress
+            .current_file
+            .as_ref()
+            .map(|f| f.display().to_string().replace('\\', "/"))
+            .unwrap_or_default();
 
-        let widget = Paragraph::new(progress_text).block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Analysis in Progress"),
-        );
-        frame.render_widget(widget, area);
+        let lines = vec![
+            Line::from(format!("Query: {}", query)),
+            Line::from(format!(
+                "Data: {} / {}",
+                format_bytes(progress.bytes_done),
+                format_bytes(progress.total_bytes)
+            )),
+            Line::from(format!(
+                "Throughput: {}/s, {:.1} files/s",
+                format_bytes(progress.bytes_per_sec() as u64),
+                progress.files_per_sec()
+            )),
+            Line::from(format!(
+                "Elapsed: {:.1}s, ETA: {}",
+                progress.elapsed.as_secs_f64(),
+                eta_text
+            )),
+            Line::from(format!("Current: {}", current_file)),
+            Line::from(""),
+            Line::from("Esc: Cancel and show results so far"),
+        ];
+
+        let stats =
+            Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Statistics"));
+        frame.render_widget(stats, stats_area);
     }
 
     fn render_error(&self, frame: &mut Frame, message: &str) {
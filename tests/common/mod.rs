@@ -0,0 +1,23 @@
+// Fixtures shared by the integration tests
+
+use std::{fs, path::PathBuf};
+
+use tempfile::TempDir;
+
+/// Unique directory with one file per (name, content), removed when dropped.
+/// Names may contain subdirectories
+pub fn temp_corpus(files: &[(&str, &str)]) -> (TempDir, Vec<PathBuf>) {
+    let dir = tempfile::tempdir().expect("create temp dir");
+    let paths = files
+        .iter()
+        .map(|(name, content)| {
+            let path = dir.path().join(name);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).expect("create temp subdir");
+            }
+            fs::write(&path, content).expect("write temp file");
+            path
+        })
+        .collect();
+    (dir, paths)
+}
@@ -1,18 +1,225 @@
-use doc_simfinder::config::Config;
+use doc_simfinder::config::{Config, SimilarityAlgorithm};
 use std::path::PathBuf;
 
+mod common;
+
 #[test]
 fn test_config_validate_empty_query() {
-    let mut cfg = Config::default();
-    cfg.search_path = PathBuf::from("testdata");
-    cfg.query = "".to_string();
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "".to_string(),
+        ..Default::default()
+    };
     assert!(cfg.validate().is_err());
 }
 
 #[test]
 fn test_config_validate_ok() {
-    let mut cfg = Config::default();
-    cfg.search_path = PathBuf::from("testdata");
-    cfg.query = "x".to_string();
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "x".to_string(),
+        ..Default::default()
+    };
     assert!(cfg.validate().is_ok());
 }
+
+#[test]
+fn test_config_errors_name_field_and_value() {
+    use doc_simfinder::{config::ConfigField, errors::ConfigError};
+
+    let valid = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "x".to_string(),
+        ..Default::default()
+    };
+
+    let cfg = Config {
+        query: "".to_string(),
+        ..valid.clone()
+    };
+    assert_eq!(cfg.validate(), Err(ConfigError::MissingQuery));
+
+    let cfg = Config {
+        threshold: 1.5,
+        ..valid.clone()
+    };
+    assert_eq!(cfg.validate(), Err(ConfigError::ThresholdOutOfRange(1.5)));
+    assert_eq!(
+        cfg.validate().unwrap_err().to_string(),
+        "Threshold 1.5 is out of range [0, 1]"
+    );
+
+    let cfg = Config {
+        window_size: 800,
+        max_window_size: 600,
+        ..valid.clone()
+    };
+    let err = cfg.validate().unwrap_err();
+    assert_eq!(
+        err,
+        ConfigError::WindowTooLarge {
+            window: 800,
+            max: 600
+        }
+    );
+    assert_eq!(err.field(), ConfigField::MaxWindowSize);
+
+    let cfg = Config {
+        file_exts: vec![".txt".to_string(), ".exe".to_string()],
+        ..valid.clone()
+    };
+    assert_eq!(
+        cfg.validate(),
+        Err(ConfigError::UnsupportedExtension(".exe".to_string()))
+    );
+
+    let cfg = Config {
+        query: "[unclosed".to_string(),
+        algorithm: SimilarityAlgorithm::Regex,
+        ..valid.clone()
+    };
+    let err = cfg.validate().unwrap_err();
+    assert!(matches!(err, ConfigError::InvalidRegex(_)));
+    assert!(!err.to_string().contains('\n'));
+}
+
+#[test]
+fn test_config_collects_all_errors() {
+    use doc_simfinder::errors::ConfigError;
+
+    let cfg = Config {
+        search_path: PathBuf::from("testdata/does-not-exist"),
+        query: "".to_string(),
+        top_n: 0,
+        ..Default::default()
+    };
+    assert_eq!(
+        cfg.validation_errors(),
+        [
+            ConfigError::SearchPathNotFound(PathBuf::from("testdata/does-not-exist")),
+            ConfigError::MissingQuery,
+            ConfigError::TopNZero,
+        ]
+    );
+    // `validate` reports the first one
+    assert_eq!(
+        cfg.validate(),
+        Err(ConfigError::SearchPathNotFound(PathBuf::from(
+            "testdata/does-not-exist"
+        )))
+    );
+}
+
+#[test]
+fn test_config_files_and_profiles_are_layered() {
+    use clap::Parser;
+    use doc_simfinder::{
+        cli::{CliArgs, resolve_config},
+        config::{ChunkingStrategy, file::ValueSource},
+        presentation::present_effective_config,
+    };
+
+    let (dir, mut files) = common::temp_corpus(&[
+        ("user.toml", "algorithm = \"bm25\"\nthreshold = 0.9\n"),
+        (
+            "project.toml",
+            r#"
+threshold = 0.6
+
+[profile.code]
+file_exts = [".rs", ".py"]
+window_size = 300
+chunking = "structure"
+"#,
+        ),
+    ]);
+    let (user, project) = (files[0].clone(), files[1].clone());
+    files.push(dir.path().join("missing.toml"));
+
+    let args =
+        CliArgs::try_parse_from(["doc-simfinder", "--profile", "code", "--window-size", "250"])
+            .unwrap();
+    let (config, sources) = resolve_config(&args, &files).expect("config should load");
+
+    // Project overrides user, profile overrides both, flags override everything
+    assert!(matches!(config.algorithm, SimilarityAlgorithm::Bm25));
+    assert_eq!(config.threshold, 0.6);
+    assert_eq!(config.file_exts, [".rs", ".py"]);
+    assert_eq!(config.chunking, ChunkingStrategy::Structure);
+    assert_eq!(config.window_size, 250);
+    assert_eq!(config.top_n, Config::default().top_n);
+
+    assert_eq!(sources.get("algorithm"), &ValueSource::File(user.clone()));
+    assert_eq!(
+        sources.get("threshold"),
+        &ValueSource::File(project.clone())
+    );
+    assert_eq!(
+        sources.get("file_exts"),
+        &ValueSource::Profile {
+            name: "code".to_string(),
+            file: project.clone(),
+        }
+    );
+    assert_eq!(sources.get("window_size"), &ValueSource::CommandLine);
+    assert_eq!(sources.get("top_n"), &ValueSource::Default);
+
+    let shown = present_effective_config(&config, &sources);
+    assert!(shown.contains("window_size = 250"));
+    assert!(shown.contains("# command line"));
+    assert!(shown.contains(&format!("# profile 'code' in {}", project.display())));
+}
+
+#[test]
+fn test_config_file_errors() {
+    use clap::Parser;
+    use doc_simfinder::{
+        cli::{CliArgs, resolve_config},
+        errors::ConfigFileError,
+    };
+
+    let (_dir, files) = common::temp_corpus(&[("doc-simfinder.toml", "threshold = 0.5\n")]);
+    let file = &files[0];
+
+    let args = CliArgs::try_parse_from(["doc-simfinder", "--profile", "nope"]).unwrap();
+    assert!(matches!(
+        resolve_config(&args, std::slice::from_ref(file)),
+        Err(ConfigFileError::UnknownProfile(name)) if name == "nope"
+    ));
+
+    // Typos are reported instead of silently ignored
+    std::fs::write(file, "treshold = 0.5\n").unwrap();
+    let args = CliArgs::try_parse_from(["doc-simfinder"]).unwrap();
+    assert!(matches!(
+        resolve_config(&args, std::slice::from_ref(file)),
+        Err(ConfigFileError::Parse { .. })
+    ));
+}
+
+#[test]
+fn test_cli_output_flags() {
+    use clap::Parser;
+    use doc_simfinder::{
+        cli::{CliArgs, resolve_config},
+        presentation::MatchDetail,
+    };
+
+    let args = CliArgs::try_parse_from(["doc-simfinder", "-n", "5", "--count"]).unwrap();
+    let (config, _) = resolve_config(&args, &[]).unwrap();
+    assert_eq!(config.top_n, 5);
+    assert_eq!(args.match_detail(), MatchDetail::Count);
+
+    let args = CliArgs::try_parse_from(["doc-simfinder", "--files-only", "--no-color"]).unwrap();
+    assert_eq!(args.match_detail(), MatchDetail::FilesOnly);
+    assert!(!args.use_color());
+
+    // Both print the matches differently, and replace the --format output
+    assert!(CliArgs::try_parse_from(["doc-simfinder", "--files-only", "--count"]).is_err());
+    assert!(CliArgs::try_parse_from(["doc-simfinder", "--count", "--format", "json"]).is_err());
+    // Duplicate clusters have no report format
+    assert!(CliArgs::try_parse_from(["doc-simfinder", "--dups", "-o", "dups.md"]).is_err());
+    // A reference document replaces the query, both at once is a mistake
+    assert!(
+        CliArgs::try_parse_from(["doc-simfinder", "--like", "a.md", "--query", "rust"]).is_err()
+    );
+}
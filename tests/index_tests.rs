@@ -0,0 +1,170 @@
+use std::{fs, path::PathBuf};
+
+use doc_simfinder::{
+    analysis::{AnalysisReport, analyse_files},
+    config::{Config, SimilarityAlgorithm},
+    index::IndexStats,
+};
+
+mod common;
+
+fn index_stats(report: &AnalysisReport) -> IndexStats {
+    match &report.index {
+        Some(Ok(stats)) => *stats,
+        other => panic!("expected index stats, got {:?}", other),
+    }
+}
+
+// Path, file score and top chunk texts, in path order
+fn summary(report: &AnalysisReport) -> Vec<(PathBuf, f64, Vec<String>)> {
+    let mut summary: Vec<_> = report
+        .scores
+        .iter()
+        .map(|s| {
+            let chunks = s.top_chunks.iter().map(|c| c.chunk.text.clone()).collect();
+            (s.path.clone(), s.score, chunks)
+        })
+        .collect();
+    summary.sort_by(|a, b| a.0.cmp(&b.0));
+    summary
+}
+
+#[test]
+fn test_index_reuses_unchanged_files() {
+    let (dir, files) = common::temp_corpus(&[
+        ("a.txt", "Retry the connection with exponential backoff."),
+        ("b.txt", "Nothing relevant in here at all."),
+    ]);
+    let mut config = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "exponential backoff".to_string(),
+        threshold: 0.0,
+        ..Default::default()
+    };
+    let unindexed = analyse_files(&files, &config).unwrap();
+    assert!(unindexed.index.is_none());
+
+    config.index_file = Some(dir.path().join("cache/index.bin"));
+    let first = analyse_files(&files, &config).unwrap();
+    assert_eq!(
+        index_stats(&first),
+        IndexStats {
+            files: 2,
+            reused: 0,
+            indexed: 2,
+            removed: 0,
+        }
+    );
+    assert!(dir.path().join("cache/index.bin").is_file());
+
+    let second = analyse_files(&files, &config).unwrap();
+    assert_eq!(index_stats(&second).reused, 2);
+    assert_eq!(index_stats(&second).indexed, 0);
+    assert_eq!(summary(&second), summary(&unindexed));
+
+    // Size changes even if the modification time doesn't
+    fs::write(
+        &files[0],
+        "Retry the connection with exponential backoff, twice.",
+    )
+    .unwrap();
+    let changed = analyse_files(&files, &config).unwrap();
+    assert_eq!(index_stats(&changed).reused, 1);
+    assert_eq!(index_stats(&changed).indexed, 1);
+    assert!(
+        changed.scores[0].top_chunks[0]
+            .chunk
+            .text
+            .ends_with("twice.")
+    );
+
+    fs::remove_file(&files[1]).unwrap();
+    let pruned = analyse_files(&files[..1], &config).unwrap();
+    assert_eq!(index_stats(&pruned).files, 1);
+    assert_eq!(index_stats(&pruned).removed, 1);
+}
+
+#[test]
+fn test_index_rechunks_stored_text() {
+    let text = "word ".repeat(100);
+    let (dir, files) = common::temp_corpus(&[("a.txt", &text)]);
+    let mut config = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "word".to_string(),
+        threshold: 0.0,
+        window_size: 100,
+        index_file: Some(dir.path().join("index.bin")),
+        ..Default::default()
+    };
+    analyse_files(&files, &config).unwrap();
+
+    // Other chunking parameters don't read the file again
+    config.window_size = 50;
+    let report = analyse_files(&files, &config).unwrap();
+    assert_eq!(index_stats(&report).reused, 1);
+    assert_eq!(index_stats(&report).indexed, 0);
+    assert!(report.scores[0].top_chunks[0].chunk.text.chars().count() <= 50);
+
+    config.index_file = None;
+    assert_eq!(
+        summary(&report),
+        summary(&analyse_files(&files, &config).unwrap())
+    );
+}
+
+#[test]
+fn test_index_bm25_scores_match_unindexed() {
+    let (dir, files) = common::temp_corpus(&[
+        (
+            "a.md",
+            "Tokio runtime and async tasks.\n\nAsync IO with tokio.",
+        ),
+        ("b.md", "Threads and channels, no async here."),
+        ("c.md", "A runtime for tasks."),
+    ]);
+    let mut config = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "async tokio runtime".to_string(),
+        algorithm: SimilarityAlgorithm::Bm25,
+        threshold: 0.0,
+        window_size: 30,
+        ..Default::default()
+    };
+    let unindexed = analyse_files(&files, &config).unwrap();
+
+    // Corpus statistics come from the index on the second run
+    config.index_file = Some(dir.path().join("index.bin"));
+    analyse_files(&files, &config).unwrap();
+    let indexed = analyse_files(&files, &config).unwrap();
+    assert_eq!(index_stats(&indexed).reused, 3);
+    assert_eq!(summary(&indexed), summary(&unindexed));
+}
+
+#[test]
+fn test_index_rebuilds_unreadable_index() {
+    let (dir, files) = common::temp_corpus(&[("a.txt", "some text")]);
+    let index = dir.path().join("index.bin");
+    fs::write(&index, b"not an index").unwrap();
+
+    let config = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "text".to_string(),
+        index_file: Some(index.clone()),
+        ..Default::default()
+    };
+    let report = analyse_files(&files, &config).unwrap();
+    assert_eq!(index_stats(&report).indexed, 1);
+    assert_eq!(
+        index_stats(&analyse_files(&files, &config).unwrap()).reused,
+        1
+    );
+
+    // A directory can't be read as an index, the analysis still runs
+    let config = Config {
+        index_file: Some(dir.path().to_path_buf()),
+        ..config
+    };
+    let report = analyse_files(&files, &config).unwrap();
+    assert_eq!(report.scores.len(), 1);
+    assert!(matches!(report.index, Some(Err(_))));
+}
@@ -1,12 +1,20 @@
 use std::path::PathBuf;
 
-use doc_simfinder::{analysis::score_file, config::Config, file_walker::walk_from_root};
+use doc_simfinder::{
+    analysis::{analyse_files, dups::find_duplicates, score_file},
+    config::{ChunkingStrategy, CodeScope, Config, ScoreAggregation, SimilarityAlgorithm},
+    file_walker::walk_from_root,
+};
+
+mod common;
 
 #[test]
 fn test_walk_from_root() {
-    let mut cfg = Config::default();
-    cfg.search_path = PathBuf::from("testdata");
-    cfg.max_search_depth = 3;
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        max_search_depth: 3,
+        ..Default::default()
+    };
 
     let res = walk_from_root(&cfg).expect("walk failed");
     assert!(res.files.len() >= 2, "expected at least 2 files in testdata");
@@ -14,11 +22,13 @@ fn test_walk_from_root() {
 
 #[test]
 fn test_score_file_on_sample() {
-    let mut cfg = Config::default();
-    cfg.search_path = PathBuf::from("testdata");
-    cfg.query = "Lorem ipsum".to_string();
-    cfg.window_size = 200;
-    cfg.num_threads = 1;
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "Lorem ipsum".to_string(),
+        window_size: 200,
+        num_threads: 1,
+        ..Default::default()
+    };
 
     let walk = walk_from_root(&cfg).expect("walk failed");
     // find file00.txt
@@ -32,3 +42,821 @@ fn test_score_file_on_sample() {
     // Expect a non-zero score for this query in file00.txt
     assert!(score.score > 0.0 || !score.top_chunks.is_empty(), "expected some matches");
 }
+
+#[test]
+fn test_score_file_lcs() {
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "LOREM IPSUM".to_string(),
+        algorithm: SimilarityAlgorithm::LCS,
+        window_size: 200,
+        ..Default::default()
+    };
+
+    let score = score_file(&PathBuf::from("testdata/file00.txt"), &cfg).expect("scoring failed");
+    // Exact (case-insensitive) occurrence of the whole query
+    assert_eq!(score.score, 1.0);
+
+    let top = &score.top_chunks[0];
+    let indices = top.indices.as_ref().expect("expected matched indices");
+    assert_eq!(indices.len(), "LOREM IPSUM".len());
+    assert!(
+        indices.windows(2).all(|w| w[1] == w[0] + 1),
+        "indices must be contiguous"
+    );
+
+    let matched: String = top
+        .chunk
+        .text
+        .chars()
+        .skip(indices[0])
+        .take(indices.len())
+        .collect();
+    assert_eq!(matched, "Lorem ipsum");
+}
+
+#[test]
+fn test_analyse_files_bm25() {
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "another string".to_string(),
+        algorithm: SimilarityAlgorithm::Bm25,
+        threshold: 0.1,
+        ..Default::default()
+    };
+
+    let walk = walk_from_root(&cfg).expect("walk failed");
+    let scores = analyse_files(&walk.files, &cfg)
+        .expect("analysis failed")
+        .scores;
+
+    let md = scores
+        .iter()
+        .find(|s| s.path.ends_with("file01.md"))
+        .expect("file01.md not scored");
+    assert!(md.score > 0.0, "expected file01.md to match");
+
+    // Indices point at the matched terms so highlighting keeps working
+    let top = &md.top_chunks[0];
+    let indices = top.indices.as_ref().expect("expected matched indices");
+    let highlighted: String = top
+        .chunk
+        .text
+        .chars()
+        .enumerate()
+        .filter(|(i, _)| indices.contains(i))
+        .map(|(_, c)| c.to_ascii_lowercase())
+        .collect();
+    assert!(highlighted.contains("another"));
+    assert!(highlighted.contains("string"));
+}
+
+#[test]
+fn test_analyse_files_like_reference() {
+    let reference = PathBuf::from("testdata/another_dir/large.txt");
+    let cfg = Config {
+        search_path: PathBuf::from("testdata/another_dir"),
+        reference_file: Some(reference.clone()),
+        threshold: 0.5,
+        ..Default::default()
+    };
+    assert!(
+        cfg.validate().is_ok(),
+        "query is optional with a reference file"
+    );
+
+    let walk = walk_from_root(&cfg).expect("walk failed");
+    let scores = analyse_files(&walk.files, &cfg)
+        .expect("analysis failed")
+        .scores;
+
+    // The reference itself is not reported
+    assert!(scores.iter().all(|s| !s.path.ends_with("large.txt")));
+
+    // Results are ranked, copies of the reference come first
+    assert!(scores.windows(2).all(|w| w[0].score >= w[1].score));
+    let best = &scores[0];
+    assert!(best.path.to_string_lossy().contains("large copy"));
+    assert!(best.score > 0.99);
+    assert!(best.top_chunks[0].reference_chunk.is_some());
+}
+
+#[test]
+fn test_analyse_files_like_respects_code_scope() {
+    let (dir, files) = common::temp_corpus(&[
+        (
+            "reference.rs",
+            "// Retry the connection with exponential backoff\nfn parse_config(input: &str) -> usize { input.len() }\n",
+        ),
+        (
+            "same_comment.rs",
+            "// Retry the connection with exponential backoff\nfn render_widget(frame: &mut Frame) { frame.clear() }\n",
+        ),
+        (
+            "same_code.rs",
+            "// Draw dashboard widgets on screen\nfn parse_config(input: &str) -> usize { input.len() }\n",
+        ),
+    ]);
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        reference_file: Some(files[0].clone()),
+        code_scope: CodeScope::Comments,
+        threshold: 0.0,
+        ..Default::default()
+    };
+
+    let scores = analyse_files(&files, &cfg).expect("analysis failed").scores;
+    let score_of = |name: &str| {
+        scores
+            .iter()
+            .find(|s| s.path.ends_with(name))
+            .unwrap()
+            .score
+    };
+
+    // Only the comments are compared, the shared code doesn't count
+    assert!(score_of("same_comment.rs") > 0.99);
+    assert_eq!(score_of("same_code.rs"), 0.0);
+}
+
+#[test]
+fn test_find_duplicates() {
+    let cfg = Config {
+        search_path: PathBuf::from("testdata/another_dir"),
+        duplicates: true,
+        ..Default::default()
+    };
+    assert!(
+        cfg.validate().is_ok(),
+        "query is optional in duplicates mode"
+    );
+
+    let walk = walk_from_root(&cfg).expect("walk failed");
+    let report = find_duplicates(&walk.files, &cfg).expect("duplicate detection failed");
+    assert!(report.failures.is_empty());
+    let clusters = report.clusters;
+
+    assert_eq!(
+        clusters.len(),
+        1,
+        "all copies of large.txt form one cluster"
+    );
+    let cluster = &clusters[0];
+    assert!(cluster.files.iter().any(|f| f.ends_with("large.txt")));
+    assert!(cluster.files.iter().all(|f| !f.ends_with("file01.md")));
+
+    let n = cluster.files.len();
+    assert_eq!(cluster.pairs.len(), n * (n - 1) / 2);
+    assert!(cluster.pairs.iter().all(|p| p.similarity >= cfg.threshold));
+
+    // Whole files are compared, chunking and code scope don't matter
+    let scoped = Config {
+        window_size: 7,
+        code_scope: CodeScope::Comments,
+        ..cfg
+    };
+    let scoped_clusters = find_duplicates(&walk.files, &scoped)
+        .expect("duplicate detection failed")
+        .clusters;
+    assert_eq!(scoped_clusters.len(), 1);
+    assert_eq!(scoped_clusters[0].files, cluster.files);
+}
+
+#[test]
+fn test_score_file_regex() {
+    let mut cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: r"t\w+t string".to_string(),
+        algorithm: SimilarityAlgorithm::Regex,
+        ..Default::default()
+    };
+    assert!(cfg.validate().is_ok());
+
+    let score =
+        score_file(&PathBuf::from("testdata/another_dir/file01.md"), &cfg).expect("scoring failed");
+    assert!(
+        score.score >= 0.75,
+        "a single match passes the default threshold"
+    );
+
+    let top = &score.top_chunks[0];
+    let indices = top.indices.as_ref().expect("expected matched indices");
+    let matched: String = indices
+        .iter()
+        .map(|&i| top.chunk.text.chars().nth(i).unwrap())
+        .collect();
+    assert_eq!(matched, "test string");
+
+    cfg.query = "(unclosed".to_string();
+    assert!(cfg.validate().is_err());
+}
+
+#[test]
+fn test_score_file_regex_non_ascii_indices() {
+    let (dir, files) = common::temp_corpus(&[("unicode.txt", "héllo wörld, wörld")]);
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "w.rld".to_string(),
+        algorithm: SimilarityAlgorithm::Regex,
+        ..Default::default()
+    };
+
+    let score = score_file(&files[0], &cfg).expect("scoring failed");
+    let indices = score.top_chunks[0]
+        .indices
+        .clone()
+        .expect("expected indices");
+    // Character (not byte) positions of both matches
+    let expected: Vec<usize> = (6..11).chain(13..18).collect();
+    assert_eq!(indices, expected);
+}
+
+#[test]
+fn test_edit_distance_tolerates_typos() {
+    use doc_simfinder::analysis::edit_distance::{damerau_levenshtein, word_similarity};
+
+    let chars = |s: &str| s.chars().collect::<Vec<_>>();
+    // Adjacent transposition costs a single edit
+    assert_eq!(damerau_levenshtein(&chars("recieve"), &chars("receive")), 1);
+    assert_eq!(damerau_levenshtein(&chars("kitten"), &chars("sitting")), 3);
+    assert_eq!(word_similarity("same", "same"), 1.0);
+
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "anothr fiel".to_string(),
+        algorithm: SimilarityAlgorithm::EditDistance,
+        threshold: 0.7,
+        ..Default::default()
+    };
+
+    let score =
+        score_file(&PathBuf::from("testdata/another_dir/file01.md"), &cfg).expect("scoring failed");
+    assert!(score.score >= 0.7);
+
+    let top = &score.top_chunks[0];
+    let indices = top.indices.as_ref().expect("expected matched indices");
+    let matched: String = indices
+        .iter()
+        .map(|&i| top.chunk.text.chars().nth(i).unwrap())
+        .collect();
+    assert_eq!(matched.to_lowercase(), "anotherfile");
+}
+
+#[test]
+fn test_score_aggregation_strategies() {
+    // One chunk per section, LCS chunk scores 5/6, 4/6 and 0
+    let (dir, files) = common::temp_corpus(&[(
+        "aggregation.md",
+        "# A\n\nneedl\n\n# B\n\nneed\n\n# C\n\nxyz\n",
+    )]);
+
+    let score_with = |aggregation| {
+        let cfg = Config {
+            search_path: dir.path().to_path_buf(),
+            query: "needle".to_string(),
+            algorithm: SimilarityAlgorithm::LCS,
+            chunking: ChunkingStrategy::Structure,
+            threshold: 0.5,
+            top_n: 3,
+            aggregation,
+            ..Default::default()
+        };
+        score_file(&files[0], &cfg).expect("scoring failed").score
+    };
+    let assert_score = |aggregation, expected: f64| {
+        let score = score_with(aggregation);
+        assert!(
+            (score - expected).abs() < 1e-9,
+            "{:?}: expected {}, got {}",
+            aggregation,
+            expected,
+            score
+        );
+    };
+
+    assert_score(ScoreAggregation::Max, 5.0 / 6.0);
+    // Two matches in three slots, the empty one counts as 0
+    assert_score(ScoreAggregation::MeanTopK, (5.0 / 6.0 + 4.0 / 6.0) / 3.0);
+    assert_score(
+        ScoreAggregation::DiminishingSum,
+        1.0 - (1.0 / 6.0) * (2.0 / 6.0),
+    );
+    // Two of three chunks match
+    assert_score(
+        ScoreAggregation::Coverage,
+        5.0 / 6.0 * (0.5 + 0.5 * 2.0 / 3.0),
+    );
+}
+
+#[test]
+fn test_sentence_chunking_respects_boundaries() {
+    let text = "First sentence here. Second one follows!\n\nA new paragraph starts. \
+                It has a sentence that is quite a bit longer than the others.";
+    let (dir, files) = common::temp_corpus(&[("sentences.txt", text)]);
+    let path = &files[0];
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "paragraph".to_string(),
+        algorithm: SimilarityAlgorithm::LCS,
+        chunking: ChunkingStrategy::Sentence,
+        window_size: 45,
+        threshold: 0.0,
+        top_n: 10,
+        ..Default::default()
+    };
+
+    let score = score_file(path, &cfg).expect("scoring failed");
+    let texts: Vec<&str> = score
+        .top_chunks
+        .iter()
+        .map(|c| c.chunk.text.as_str())
+        .collect();
+
+    // Sentences never split, the last one exceeds the window and is cut
+    assert!(texts.contains(&"First sentence here. Second one follows!"));
+    assert!(texts.contains(&"A new paragraph starts."));
+    for chunk in &score.top_chunks {
+        assert_eq!(
+            chunk.chunk.text,
+            &text[chunk.chunk.start_byte..chunk.chunk.end_byte]
+        );
+    }
+}
+
+#[test]
+fn test_markdown_chunking_heading_breadcrumbs() {
+    let text = "# Setup\n\nIntro text.\n\n## Prerequisites\n\nInstall the toolchain first.\n\n\
+                ```sh\n# not a heading\ncargo build\n```\n\n# Usage\n\nRun it.\n";
+    let (dir, files) = common::temp_corpus(&[("structure.md", text)]);
+    let path = &files[0];
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "toolchain".to_string(),
+        algorithm: SimilarityAlgorithm::LCS,
+        chunking: ChunkingStrategy::Structure,
+        threshold: 0.0,
+        top_n: 10,
+        ..Default::default()
+    };
+
+    let score = score_file(path, &cfg).expect("scoring failed");
+    let best = &score.top_chunks[0];
+    assert_eq!(
+        best.chunk.heading_path.as_deref(),
+        Some("Setup > Prerequisites")
+    );
+    // The fenced block stays with its section, its comment is not a heading
+    assert!(best.chunk.text.contains("cargo build\n```"));
+
+    let sections: Vec<Option<&str>> = score
+        .top_chunks
+        .iter()
+        .map(|c| c.chunk.heading_path.as_deref())
+        .collect();
+    assert!(sections.contains(&Some("Setup")));
+    assert!(sections.contains(&Some("Usage")));
+    assert!(
+        !sections
+            .iter()
+            .flatten()
+            .any(|s| s.contains("not a heading"))
+    );
+}
+
+#[test]
+fn test_code_chunking_symbols_and_scope() {
+    let source = r#"use std::fmt;
+
+/// Parses the retry configuration
+fn parse_retry(input: &str) -> usize {
+    input.len()
+}
+
+struct Backoff {
+    delay: u64,
+}
+
+fn log_message() {
+    println!("retry later");
+}
+"#;
+    let (dir, files) = common::temp_corpus(&[("code.rs", source)]);
+    let path = &files[0];
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "retry".to_string(),
+        algorithm: SimilarityAlgorithm::LCS,
+        chunking: ChunkingStrategy::Structure,
+        window_size: 60,
+        threshold: 0.5,
+        top_n: 10,
+        ..Default::default()
+    };
+
+    let score = score_file(path, &cfg).expect("scoring failed");
+    let symbols: Vec<Option<&str>> = score
+        .top_chunks
+        .iter()
+        .map(|c| c.chunk.symbol.as_deref())
+        .collect();
+    assert!(symbols.contains(&Some("parse_retry")));
+    assert!(symbols.contains(&Some("log_message")));
+    // The doc comment stays with the function it documents
+    let parse = score
+        .top_chunks
+        .iter()
+        .find(|c| c.chunk.symbol.as_deref() == Some("parse_retry"))
+        .unwrap();
+    assert!(parse.chunk.text.starts_with("/// Parses"));
+
+    // Restricted to string literals only the println! argument matches
+    let strings = Config {
+        code_scope: CodeScope::Strings,
+        ..cfg.clone()
+    };
+    let score = score_file(path, &strings).expect("scoring failed");
+    assert_eq!(score.top_chunks.len(), 1);
+    assert_eq!(
+        score.top_chunks[0].chunk.symbol.as_deref(),
+        Some("log_message")
+    );
+
+    // Restricted to identifiers the comment and string no longer count
+    let identifiers = Config {
+        query: "parse_retry".to_string(),
+        code_scope: CodeScope::Identifiers,
+        ..cfg
+    };
+    let score = score_file(path, &identifiers).expect("scoring failed");
+    assert!(
+        score
+            .top_chunks
+            .iter()
+            .all(|c| c.chunk.symbol.as_deref() == Some("parse_retry"))
+    );
+}
+
+#[test]
+fn test_code_scope_skips_prose_files() {
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "file".to_string(),
+        code_scope: CodeScope::Comments,
+        threshold: 0.0,
+        ..Default::default()
+    };
+
+    let score = score_file(&PathBuf::from("testdata/file00.txt"), &cfg).expect("scoring failed");
+    assert!(score.top_chunks.is_empty());
+}
+
+#[test]
+fn test_chunk_byte_offsets_and_positions() {
+    let text = "Erste Zeile mit Umläuten\nzweite Zeile: äöü Ziel hier\n";
+    let (dir, files) = common::temp_corpus(&[("positions.txt", text)]);
+    let path = &files[0];
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "Ziel".to_string(),
+        algorithm: SimilarityAlgorithm::LCS,
+        window_size: 20,
+        threshold: 0.9,
+        ..Default::default()
+    };
+
+    let score = score_file(path, &cfg).expect("scoring failed");
+    let best = &score.top_chunks[0];
+
+    // Offsets are bytes into the file, not chars
+    assert_eq!(
+        &text[best.chunk.start_byte..best.chunk.end_byte],
+        best.chunk.text
+    );
+    assert_eq!(best.position(), (2, 19));
+}
+
+#[test]
+fn test_vimgrep_columns_count_bytes() {
+    use doc_simfinder::presentation::present_vimgrep;
+
+    // Multibyte chars before the chunk start and before the match in the chunk
+    let text = "Größe und Gewicht: äöü Ziel hier\n";
+    let (dir, files) = common::temp_corpus(&[("vimgrep.txt", text)]);
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "Ziel".to_string(),
+        algorithm: SimilarityAlgorithm::Regex,
+        window_size: 20,
+        top_n: 1,
+        ..Default::default()
+    };
+    let score = score_file(&files[0], &cfg).expect("scoring failed");
+    let best = &score.top_chunks[0];
+    assert!(best.chunk.start_byte > 0);
+
+    // Chars for people, bytes for editors
+    let column = text.find("Ziel").unwrap() + 1;
+    assert_eq!(best.position(), (1, 24));
+    assert_eq!(best.byte_position(), (1, column));
+    let out = present_vimgrep(std::slice::from_ref(&score), &cfg);
+    assert!(out.starts_with(&format!("{}:1:{}:", files[0].display(), column)));
+}
+
+#[test]
+fn test_vimgrep_prints_each_match_once() {
+    use doc_simfinder::presentation::present_vimgrep;
+
+    // Windows of 40 chars overlap by 4, "Ziel" is at the start of the second
+    // chunk and the end of the first
+    let text = format!("{}Ziel{}\n", "a".repeat(36), "b".repeat(30));
+    let (dir, files) = common::temp_corpus(&[("overlap.txt", &text)]);
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "Ziel".to_string(),
+        algorithm: SimilarityAlgorithm::Regex,
+        window_size: 40,
+        ..Default::default()
+    };
+    let score = score_file(&files[0], &cfg).expect("scoring failed");
+    assert_eq!(score.top_chunks.len(), 2);
+
+    let out = present_vimgrep(std::slice::from_ref(&score), &cfg);
+    assert_eq!(out, format!("{}:1:37:{}", files[0].display(), text));
+}
+
+#[test]
+fn test_vimgrep_prints_whole_source_lines() {
+    use doc_simfinder::presentation::present_vimgrep;
+
+    // One line much longer than the window, the match in a chunk starting mid-line
+    let line = format!(
+        "{} Ziel {}",
+        "lorem ipsum ".repeat(20),
+        "dolor sit ".repeat(20)
+    );
+    let text = format!("first line\n{}\nlast line\n", line);
+    let (dir, files) = common::temp_corpus(&[("long.txt", &text)]);
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "Ziel".to_string(),
+        algorithm: SimilarityAlgorithm::Regex,
+        window_size: 50,
+        ..Default::default()
+    };
+    let score = score_file(&files[0], &cfg).expect("scoring failed");
+    assert!(score.top_chunks[0].chunk.column > 1);
+
+    let column = line.find("Ziel").unwrap() + 1;
+    let out = present_vimgrep(std::slice::from_ref(&score), &cfg);
+    assert_eq!(
+        out,
+        format!("{}:2:{}:{}\n", files[0].display(), column, line)
+    );
+}
+
+#[test]
+fn test_thread_pool_honors_num_threads() {
+    use doc_simfinder::analysis::with_thread_pool;
+
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "file".to_string(),
+        threshold: 0.0,
+        num_threads: 2,
+        ..Default::default()
+    };
+
+    assert_eq!(with_thread_pool(&cfg, rayon::current_num_threads), 2);
+    // Nested calls reuse the pool they run on
+    assert_eq!(
+        with_thread_pool(&cfg, || with_thread_pool(
+            &Config {
+                num_threads: 3,
+                ..cfg.clone()
+            },
+            rayon::current_num_threads
+        )),
+        2
+    );
+
+    let walk = walk_from_root(&cfg).expect("walk failed");
+    let sorted = |mut scores: Vec<doc_simfinder::analysis::FileScore>| {
+        scores.sort_by(|a, b| a.path.cmp(&b.path));
+        scores
+            .into_iter()
+            .map(|s| (s.path, s.score))
+            .collect::<Vec<_>>()
+    };
+    let single = analyse_files(
+        &walk.files,
+        &Config {
+            num_threads: 1,
+            ..cfg.clone()
+        },
+    )
+    .expect("analysis failed")
+    .scores;
+    let parallel = analyse_files(&walk.files, &cfg)
+        .expect("analysis failed")
+        .scores;
+    assert_eq!(sorted(single), sorted(parallel));
+}
+
+#[test]
+fn test_cancelled_analysis_returns_partial_results() {
+    use doc_simfinder::analysis::{AnalysisHooks, analyse_files_with};
+
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "file".to_string(),
+        threshold: 0.0,
+        ..Default::default()
+    };
+    let walk = walk_from_root(&cfg).expect("walk failed");
+
+    let hooks = AnalysisHooks::default();
+    let all = analyse_files_with(&walk.files, &cfg, &hooks)
+        .expect("analysis failed")
+        .scores;
+    assert_eq!(all.len(), walk.files.len());
+    assert!(!hooks.cancel.is_cancelled());
+
+    // Cancelled up front, no file is scored and none is reported as failed
+    hooks.cancel.cancel();
+    for algorithm in [SimilarityAlgorithm::Fuzzy, SimilarityAlgorithm::Bm25] {
+        let partial = analyse_files_with(
+            &walk.files,
+            &Config {
+                algorithm,
+                ..cfg.clone()
+            },
+            &hooks,
+        )
+        .expect("cancelled analysis should not fail")
+        .scores;
+        assert!(partial.is_empty());
+    }
+
+    // Clones share the flag
+    let token = doc_simfinder::analysis::CancellationToken::new();
+    token.clone().cancel();
+    assert!(token.is_cancelled());
+}
+
+#[test]
+fn test_analysis_reports_progress() {
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use doc_simfinder::analysis::{AnalysisHooks, Progress, analyse_files_with};
+
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "file".to_string(),
+        threshold: 0.0,
+        ..Default::default()
+    };
+    let walk = walk_from_root(&cfg).expect("walk failed");
+
+    for algorithm in [SimilarityAlgorithm::Fuzzy, SimilarityAlgorithm::Bm25] {
+        let reports = Mutex::new(Vec::<Progress>::new());
+        let on_progress = |p: &Progress| reports.lock().unwrap().push(p.clone());
+        analyse_files_with(
+            &walk.files,
+            &Config {
+                algorithm,
+                ..cfg.clone()
+            },
+            &AnalysisHooks {
+                on_progress: Some(&on_progress),
+                ..Default::default()
+            },
+        )
+        .expect("analysis failed");
+
+        let reports = reports.into_inner().unwrap();
+        // Throttled, but the final counts are always reported
+        let last = reports.last().expect("no progress reported");
+        assert_eq!(last.files_done, walk.files.len());
+        assert_eq!(last.total_files, walk.files.len());
+        assert_eq!(last.bytes_done, last.total_bytes);
+        assert!(last.total_bytes > 0);
+        assert_eq!(last.ratio(), 1.0);
+        assert_eq!(last.eta(), Some(Duration::ZERO));
+        assert!(
+            reports
+                .windows(2)
+                .all(|w| w[0].files_done <= w[1].files_done)
+        );
+    }
+
+    let halfway = Progress {
+        files_done: 1,
+        total_files: 4,
+        bytes_done: 50,
+        total_bytes: 100,
+        current_file: None,
+        elapsed: Duration::from_secs(2),
+    };
+    // Estimated by bytes, not by file count
+    assert_eq!(halfway.ratio(), 0.5);
+    assert_eq!(halfway.eta(), Some(Duration::from_secs(2)));
+    assert_eq!(halfway.bytes_per_sec(), 25.0);
+    assert_eq!(Progress::default().eta(), None);
+}
+
+#[test]
+fn test_analysis_streams_results() {
+    use std::sync::Mutex;
+
+    use doc_simfinder::analysis::{AnalysisHooks, FileScore, analyse_files_with};
+
+    let cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "file".to_string(),
+        threshold: 0.0,
+        ..Default::default()
+    };
+    let walk = walk_from_root(&cfg).expect("walk failed");
+
+    for algorithm in [SimilarityAlgorithm::Fuzzy, SimilarityAlgorithm::Bm25] {
+        let cfg = Config {
+            algorithm,
+            ..cfg.clone()
+        };
+        let streamed = Mutex::new(Vec::<FileScore>::new());
+        let on_result = |r: &FileScore| streamed.lock().unwrap().push(r.clone());
+        let results = analyse_files_with(
+            &walk.files,
+            &cfg,
+            &AnalysisHooks {
+                on_result: Some(&on_result),
+                ..Default::default()
+            },
+        )
+        .expect("analysis failed")
+        .scores;
+
+        // Every returned file was streamed before, with the same score
+        let mut streamed: Vec<_> = streamed
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.path, r.score))
+            .collect();
+        let mut returned: Vec<_> = results.into_iter().map(|r| (r.path, r.score)).collect();
+        streamed.sort_by(|a, b| a.0.cmp(&b.0));
+        returned.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(streamed, returned);
+    }
+}
+
+#[test]
+fn test_failed_files_are_reported_with_reasons() {
+    use doc_simfinder::presentation::present_skipped_files;
+
+    let (dir, files) = common::temp_corpus(&[("text.txt", "plain text about files")]);
+    let text = files[0].clone();
+    let binary = dir.path().join("binary.dat");
+    let latin1 = dir.path().join("latin1.txt");
+    std::fs::write(&binary, [0u8, 1, 2, 3, 0, 0, 255, 254]).unwrap();
+    std::fs::write(&latin1, b"caf\xe9 au lait, plain text about files").unwrap();
+
+    let cfg = Config {
+        search_path: dir.path().to_path_buf(),
+        query: "files".to_string(),
+        threshold: 0.0,
+        ..Default::default()
+    };
+    let report = analyse_files(&[text.clone(), binary.clone(), latin1.clone()], &cfg)
+        .expect("analysis failed");
+
+    assert_eq!(report.scores.len(), 1);
+    assert_eq!(report.scores[0].path, text);
+
+    let mut skipped = report.skipped();
+    skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    let reasons: Vec<_> = skipped.iter().map(|s| (&s.path, s.reason)).collect();
+    assert_eq!(reasons, [(&binary, "binary"), (&latin1, "invalid UTF-8")]);
+
+    let out = present_skipped_files(&report.failures);
+    assert!(out.contains("Skipped 2 file(s)"));
+    assert!(out.contains(&binary.display().to_string()));
+    assert!(present_skipped_files(&[]).is_empty());
+
+    // Duplicate detection reports the same files instead of printing them
+    let dups = find_duplicates(&[text, binary.clone(), latin1.clone()], &cfg)
+        .expect("duplicate detection failed");
+    assert!(dups.clusters.is_empty());
+    let mut failed: Vec<_> = dups.failures.iter().map(|(p, e)| (p, e.reason())).collect();
+    failed.sort();
+    assert_eq!(failed, [(&binary, "binary"), (&latin1, "invalid UTF-8")]);
+}
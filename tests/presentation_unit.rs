@@ -4,18 +4,27 @@ use doc_simfinder::analysis::{Chunk, FileScore, ScoredChunk};
 use doc_simfinder::config::Config;
 use doc_simfinder::presentation::present_file_score;
 
+mod common;
+
 #[test]
 fn test_present_file_score_basic() {
     let chunk = Chunk {
         text: "This is a test snippet".to_string(),
+        start_char: 0,
+        end_char: 22,
         start_byte: 0,
-        end_byte: 21,
+        end_byte: 22,
+        line: 3,
+        column: 5,
+        ..Default::default()
     };
 
     let scored = ScoredChunk {
         score: 0.75,
         chunk,
         indices: None,
+        reference_chunk: None,
+        term_scores: None,
     };
 
     let fs = FileScore {
@@ -30,4 +39,403 @@ fn test_present_file_score_basic() {
     assert!(output.contains("Top chunks"));
     assert!(output.contains("1."));
     assert!(output.contains("This is a test snippet"));
+    assert!(output.contains("test.txt:3:5"));
+}
+
+#[test]
+fn test_present_file_score_non_ascii_highlights() {
+    let chunk = Chunk {
+        text: "größe\nmaß".to_string(),
+        line: 1,
+        column: 1,
+        ..Default::default()
+    };
+
+    let scored = ScoredChunk {
+        score: 1.0,
+        chunk,
+        // "maß" on the second line, as char indices
+        indices: Some(vec![6, 7, 8]),
+        reference_chunk: None,
+        term_scores: None,
+    };
+
+    assert_eq!(scored.position(), (2, 1));
+
+    let fs = FileScore {
+        path: PathBuf::from("test.txt"),
+        score: 1.0,
+        top_chunks: vec![scored],
+        analysis_duration: None,
+    };
+
+    // Used to slice by byte offset and panic inside a multi-byte char
+    let output = present_file_score(&fs, &Config::default());
+    assert!(output.contains("test.txt:2:1"));
+}
+
+#[test]
+fn test_present_json_schema() {
+    use doc_simfinder::analysis::{SkippedFile, query::TermScore};
+    use doc_simfinder::presentation::json::{SCHEMA_VERSION, present_json, present_ndjson_line};
+
+    let chunk = Chunk {
+        text: "größe matters".to_string(),
+        start_char: 10,
+        end_char: 23,
+        start_byte: 12,
+        end_byte: 27,
+        line: 2,
+        column: 4,
+        heading_path: Some("Intro".to_string()),
+        ..Default::default()
+    };
+    let fs = FileScore {
+        path: PathBuf::from("docs/a.md"),
+        score: 0.5,
+        top_chunks: vec![ScoredChunk {
+            score: 0.5,
+            chunk,
+            indices: Some(vec![0, 1, 2]),
+            reference_chunk: None,
+            term_scores: Some(vec![TermScore {
+                term: "größe".to_string(),
+                score: 1.0,
+                negated: false,
+            }]),
+        }],
+        analysis_duration: Some(std::time::Duration::from_millis(12)),
+    };
+    let skipped = [SkippedFile {
+        path: PathBuf::from("image.png"),
+        reason: "binary",
+        message: "File appears to be binary: image.png".to_string(),
+    }];
+
+    let json: serde_json::Value = serde_json::from_str(&present_json(&[&fs], &skipped)).unwrap();
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["skipped"][0]["reason"], "binary");
+
+    let result = &json["results"][0];
+    assert_eq!(result["path"], "docs/a.md");
+    assert_eq!(result["analysis_duration_ms"], 12.0);
+    let chunk = &result["top_chunks"][0];
+    assert_eq!(chunk["text"], "größe matters");
+    assert_eq!(chunk["start_byte"], 12);
+    assert_eq!(chunk["line"], 2);
+    assert_eq!(chunk["column"], 4);
+    assert_eq!(chunk["heading_path"], "Intro");
+    assert_eq!(chunk["symbol"], serde_json::Value::Null);
+    assert_eq!(chunk["matched_indices"], serde_json::json!([0, 1, 2]));
+    assert_eq!(chunk["term_scores"][0]["term"], "größe");
+    assert_eq!(chunk["reference"], serde_json::Value::Null);
+
+    // NDJSON lines hold the same object as the results array
+    let line = present_ndjson_line(&fs);
+    assert!(!line.contains('\n'));
+    let parsed: serde_json::Value = serde_json::from_str(&line).unwrap();
+    assert_eq!(&parsed, result);
+}
+
+#[test]
+fn test_json_lists_only_matches() {
+    use doc_simfinder::presentation::{
+        best_matches,
+        json::{present_json, present_ndjson_line},
+    };
+
+    let file = |path: &str, score: f64, chunks: usize| FileScore {
+        path: PathBuf::from(path),
+        score,
+        top_chunks: (0..chunks)
+            .map(|_| ScoredChunk {
+                score,
+                chunk: Chunk::default(),
+                indices: None,
+                reference_chunk: None,
+                term_scores: None,
+            })
+            .collect(),
+        analysis_duration: None,
+    };
+    let scores = [
+        file("none.md", 0.0, 0),
+        file("good.md", 0.8, 1),
+        file("best.md", 0.9, 1),
+    ];
+    let matches = best_matches(&scores, Config::default().threshold);
+
+    // Same files as the text output, best first
+    let json: serde_json::Value = serde_json::from_str(&present_json(&matches, &[])).unwrap();
+    let paths: Vec<_> = json["results"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["path"].as_str().unwrap())
+        .collect();
+    assert_eq!(paths, ["best.md", "good.md"]);
+
+    let ndjson: Vec<_> = matches.iter().map(|s| present_ndjson_line(s)).collect();
+    assert_eq!(ndjson.len(), 2);
+    assert!(ndjson.iter().all(|line| !line.contains("none.md")));
+}
+
+#[test]
+fn test_report_formats() {
+    use doc_simfinder::analysis::SkippedFile;
+    use doc_simfinder::errors::ReportError;
+    use doc_simfinder::{
+        config::ReportFormat,
+        presentation::report::{Report, render_report, write_report},
+    };
+
+    let chunk = Chunk {
+        text: "say \"hi\", then\nleave".to_string(),
+        end_char: 20,
+        end_byte: 20,
+        line: 4,
+        column: 2,
+        heading_path: Some("Usage".to_string()),
+        ..Default::default()
+    };
+    let scores = [
+        FileScore {
+            path: PathBuf::from("docs/a.md"),
+            score: 0.8,
+            top_chunks: vec![ScoredChunk {
+                score: 0.8,
+                chunk,
+                indices: None,
+                reference_chunk: None,
+                term_scores: None,
+            }],
+            analysis_duration: None,
+        },
+        FileScore {
+            path: PathBuf::from("b.txt"),
+            score: 0.6,
+            top_chunks: vec![],
+            analysis_duration: None,
+        },
+    ];
+    let skipped = [SkippedFile {
+        path: PathBuf::from("image.png"),
+        reason: "binary",
+        message: "File appears to be binary: image.png".to_string(),
+    }];
+
+    assert_eq!(
+        ReportFormat::from_path("out.JSONL".as_ref()),
+        Some(ReportFormat::Ndjson)
+    );
+    assert_eq!(ReportFormat::from_path("out".as_ref()), None);
+
+    // Low enough for b.txt, which has no chunks (e.g. query by example)
+    let config = Config {
+        threshold: 0.5,
+        ..Default::default()
+    };
+    let report = Report {
+        scores: &scores,
+        skipped: &skipped,
+        config: &config,
+        elapsed: None,
+    };
+
+    // Quotes, commas and line breaks are quoted, files without chunks get one row
+    let csv = render_report(ReportFormat::Csv, &report);
+    let rows: Vec<&str> = csv.lines().collect();
+    assert!(rows[0].starts_with("path,file_score,rank,"));
+    assert_eq!(
+        rows[1],
+        "docs/a.md,0.8000,1,0.8000,4,2,0,20,Usage,,\"say \"\"hi\"\", then"
+    );
+    assert_eq!(rows[2], "leave\"");
+    assert_eq!(rows[3], "b.txt,0.6000,,,,,,,,,");
+
+    let markdown = render_report(ReportFormat::Markdown, &report);
+    assert!(markdown.contains("| 1 | `docs/a.md` | 0.80 |"));
+    assert!(markdown.contains("- `docs/a.md:4:2` (score 0.80) Usage"));
+    assert!(markdown.contains("  > leave"));
+    assert!(markdown.contains("- `image.png`: binary"));
+
+    let text = render_report(ReportFormat::Text, &report);
+    assert!(text.starts_with("File: docs/a.md (score: 0.80)\n  docs/a.md:4:2 (score: 0.80)\n"));
+    assert!(text.contains("binary  image.png"));
+
+    // Written atomically, the temporary file is gone
+    let (dir, _) = common::temp_corpus(&[]);
+    let path = dir.path().join("report.md");
+    write_report(&path, &report).unwrap();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), markdown);
+    assert!(!dir.path().join(".report.md.tmp").exists());
+
+    let result = write_report(&dir.path().join("report.xlsx"), &report);
+    assert!(matches!(result, Err(ReportError::UnsupportedFormat(_))));
+    let result = write_report(&dir.path().join("missing_dir/report.csv"), &report);
+    assert!(matches!(result, Err(ReportError::Io { .. })));
+}
+
+#[test]
+fn test_reports_leave_out_files_below_threshold() {
+    use doc_simfinder::{
+        config::ReportFormat,
+        presentation::report::{Report, render_report},
+    };
+
+    let file = |path: &str, score: f64, chunks: usize| FileScore {
+        path: PathBuf::from(path),
+        score,
+        top_chunks: (0..chunks)
+            .map(|_| ScoredChunk {
+                score,
+                chunk: Chunk {
+                    text: "some text".to_string(),
+                    line: 1,
+                    column: 1,
+                    ..Default::default()
+                },
+                indices: None,
+                reference_chunk: None,
+                term_scores: None,
+            })
+            .collect(),
+        analysis_duration: None,
+    };
+    let scores = [file("unrelated.md", 0.0, 0), file("match.md", 0.9, 1)];
+    let config = Config::default();
+    let report = Report {
+        scores: &scores,
+        skipped: &[],
+        config: &config,
+        elapsed: None,
+    };
+
+    for format in [
+        ReportFormat::Text,
+        ReportFormat::Json,
+        ReportFormat::Ndjson,
+        ReportFormat::Csv,
+        ReportFormat::Markdown,
+        ReportFormat::Html,
+    ] {
+        let rendered = render_report(format, &report);
+        assert!(rendered.contains("match.md"), "{:?}", format);
+        assert!(!rendered.contains("unrelated.md"), "{:?}", format);
+    }
+}
+
+#[test]
+fn test_present_matches() {
+    use doc_simfinder::presentation::{MatchDetail, present_matches};
+
+    let file = |path: &str, score: f64| FileScore {
+        path: PathBuf::from(path),
+        score,
+        top_chunks: vec![],
+        analysis_duration: None,
+    };
+    let scores = [file("low.txt", 0.2), file("b.txt", 0.8), file("a.txt", 0.9)];
+    let config = Config {
+        threshold: 0.5,
+        ..Default::default()
+    };
+
+    // Files above threshold, best first
+    assert_eq!(
+        present_matches(&scores, &config, MatchDetail::FilesOnly),
+        "a.txt\nb.txt\n"
+    );
+    assert_eq!(present_matches(&scores, &config, MatchDetail::Count), "2\n");
+
+    let output = present_matches(&scores, &config, MatchDetail::Chunks);
+    assert!(output.contains("File: a.txt"));
+    assert!(!output.contains("low.txt"));
+    assert!(output.find("a.txt") < output.find("b.txt"));
+}
+
+#[test]
+fn test_present_vimgrep() {
+    use doc_simfinder::presentation::present_vimgrep;
+
+    let scored = |text: &str, indices: Option<Vec<usize>>| ScoredChunk {
+        score: 0.9,
+        chunk: Chunk {
+            text: text.to_string(),
+            line: 10,
+            column: 4,
+            byte_column: 6,
+            ..Default::default()
+        },
+        indices,
+        reference_chunk: None,
+        term_scores: None,
+    };
+    let fs = FileScore {
+        path: PathBuf::from("docs/a.md"),
+        score: 0.9,
+        top_chunks: vec![
+            // "maß" on the second line, column in bytes
+            scored("intro\r\nüber maß\nend", Some(vec![12, 13, 14])),
+            scored("first line\nsecond", None),
+        ],
+        analysis_duration: None,
+    };
+
+    assert_eq!(
+        present_vimgrep(&[fs], &Config::default()),
+        "docs/a.md:11:7:über maß\ndocs/a.md:10:6:first line\n"
+    );
+}
+
+#[test]
+fn test_present_html() {
+    use doc_simfinder::analysis::SkippedFile;
+    use doc_simfinder::presentation::{html::present_html, report::Report};
+
+    let fs = FileScore {
+        path: PathBuf::from("docs/a b.md"),
+        score: 0.9,
+        top_chunks: vec![ScoredChunk {
+            score: 0.9,
+            chunk: Chunk {
+                text: "use <T> & größe".to_string(),
+                line: 1,
+                column: 1,
+                ..Default::default()
+            },
+            indices: Some(vec![4, 5, 6, 13, 14]),
+            reference_chunk: None,
+            term_scores: None,
+        }],
+        analysis_duration: None,
+    };
+    let skipped = [SkippedFile {
+        path: PathBuf::from("image.png"),
+        reason: "binary",
+        message: "File appears to be binary: image.png".to_string(),
+    }];
+    let config = Config {
+        query: "<T> größe".to_string(),
+        ..Default::default()
+    };
+
+    let report = Report {
+        scores: std::slice::from_ref(&fs),
+        skipped: &skipped,
+        config: &config,
+        elapsed: Some(std::time::Duration::from_millis(1500)),
+    };
+    let html = present_html(&[&fs], &report);
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<style>"));
+    assert!(html.contains("1 matching file(s) of 1, 1 skipped, 1.50s"));
+    // Matched runs are marked, the text is escaped
+    assert!(html.contains("<pre>use <mark>&lt;T&gt;</mark> &amp; grö<mark>ße</mark></pre>"));
+    assert!(html.contains("<th>query</th><td><code>&lt;T&gt; größe</code></td>"));
+    assert!(html.contains("<th>threshold</th><td><code>0.75</code></td>"));
+    // Absolute, percent-encoded link
+    assert!(html.contains("href=\"file:///"));
+    assert!(html.contains("/docs/a%20b.md\">docs/a b.md</a>"));
+    assert!(html.contains("<td>binary</td><td><code>image.png</code></td>"));
 }
@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use doc_simfinder::analysis::{
+    query::{QueryExpr, evaluate, parse_query},
+    score_file,
+};
+use doc_simfinder::config::{Config, SimilarityAlgorithm};
+use doc_simfinder::errors::QueryParseError;
+
+fn term(t: &str) -> QueryExpr {
+    QueryExpr::Term(t.to_string())
+}
+
+#[test]
+fn test_parse_phrase_and_exclusion() {
+    let expr = parse_query("\"error handling\" -panic").expect("parse failed");
+    assert_eq!(
+        expr,
+        QueryExpr::And(vec![
+            QueryExpr::Phrase("error handling".to_string()),
+            QueryExpr::Not(Box::new(term("panic"))),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_precedence() {
+    // AND binds tighter than OR, parentheses override
+    let expr = parse_query("a b OR NOT (c OR d)").expect("parse failed");
+    assert_eq!(
+        expr,
+        QueryExpr::Or(vec![
+            QueryExpr::And(vec![term("a"), term("b")]),
+            QueryExpr::Not(Box::new(QueryExpr::Or(vec![term("c"), term("d")]))),
+        ])
+    );
+}
+
+#[test]
+fn test_parse_errors() {
+    assert_eq!(parse_query("   "), Err(QueryParseError::Empty));
+    assert_eq!(
+        parse_query("\"open"),
+        Err(QueryParseError::UnterminatedQuote)
+    );
+    assert_eq!(
+        parse_query("(a b"),
+        Err(QueryParseError::UnbalancedParenthesis)
+    );
+    assert!(matches!(
+        parse_query("a OR"),
+        Err(QueryParseError::MissingOperand(_))
+    ));
+}
+
+#[test]
+fn test_evaluate_exclusion() {
+    let expr = parse_query("\"error handling\" -panic").unwrap();
+
+    let hit = evaluate(&expr, "Good Error Handling avoids crashes", 500).expect("should match");
+    assert_eq!(hit.score, 1.0);
+    assert_eq!(hit.indices, (5..19).collect::<Vec<_>>());
+    assert!(hit.terms.iter().any(|t| t.negated && t.term == "panic"));
+
+    assert!(evaluate(&expr, "error handling without a panic", 500).is_none());
+}
+
+#[test]
+fn test_evaluate_or_takes_best_alternative() {
+    let expr = parse_query("rust OR \"missing phrase\"").unwrap();
+    let hit = evaluate(&expr, "written in rust", 500).expect("should match");
+    assert_eq!(hit.score, 1.0);
+    assert_eq!(hit.indices, vec![11, 12, 13, 14]);
+}
+
+#[test]
+fn test_score_file_boolean() {
+    let file = PathBuf::from("testdata/another_dir/file01.md");
+    let mut cfg = Config {
+        search_path: PathBuf::from("testdata"),
+        query: "\"test string\" -panic".to_string(),
+        algorithm: SimilarityAlgorithm::Boolean,
+        ..Default::default()
+    };
+    assert!(cfg.validate().is_ok());
+
+    let score = score_file(&file, &cfg).expect("scoring failed");
+    assert_eq!(score.score, 1.0);
+    let terms = score.top_chunks[0]
+        .term_scores
+        .as_ref()
+        .expect("expected explanation");
+    assert_eq!(terms.len(), 2);
+
+    // The file mentions "consectetur", so excluding it drops every chunk
+    cfg.query = "\"test string\" -consectetur".to_string();
+    let score = score_file(&file, &cfg).expect("scoring failed");
+    assert!(score.top_chunks.is_empty());
+
+    cfg.query = "(unbalanced".to_string();
+    assert!(cfg.validate().is_err());
+}